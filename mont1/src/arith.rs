@@ -18,16 +18,16 @@ pub fn fe_add(result: &mut W6x64, a: &W6x64, b: &W6x64) {
     let mut carry = false;
     for i in 0..6 {
         let sum_car_a = a.v[i].overflowing_add(b.v[i]);
-        let sum_car_b = sum_car_a.0.overflowing_add(if carry { 1 } else { 0 });
+        let sum_car_b = sum_car_a.0.overflowing_add(u64::from(carry));
         sum.v[i] = sum_car_b.0;
-        carry = sum_car_a.1 | sum_car_b.1
+        carry = sum_car_a.1 | sum_car_b.1;
     }
 
     let mut trial = W6x64::default();
     let mut borrow = false;
     for i in 0..6 {
         // Note: a single overflowing_sub is sufficient because N[i]+borrow can never overflow
-        let dif_bor = sum.v[i].overflowing_sub(N[i] + if borrow { 1 } else { 0 });
+        let dif_bor = sum.v[i].overflowing_sub(N[i] + u64::from(borrow));
         trial.v[i] = dif_bor.0;
         borrow = dif_bor.1;
     }
@@ -52,9 +52,9 @@ pub fn fe_sub(result: &mut W6x64, a: &W6x64, b: &W6x64) {
     let mut borrow_sub = false;
     for i in 0..6 {
         let dif_bor_a = a.v[i].overflowing_sub(b.v[i]);
-        let dif_bor_b = dif_bor_a.0.overflowing_sub(if borrow_sub { 1 } else { 0 });
+        let dif_bor_b = dif_bor_a.0.overflowing_sub(u64::from(borrow_sub));
         diff.v[i] = dif_bor_b.0;
-        borrow_sub = dif_bor_a.1 | dif_bor_b.1
+        borrow_sub = dif_bor_a.1 | dif_bor_b.1;
     }
 
     let mask = u64::from(borrow_sub).wrapping_neg();
@@ -62,7 +62,7 @@ pub fn fe_sub(result: &mut W6x64, a: &W6x64, b: &W6x64) {
     for i in 0..6 {
         let dif_bor =
             // Note: a single overflowing_sub is sufficient because value+borrow can never overflow
-            diff.v[i].overflowing_sub((mask & CORRECTION[i]) + if borrow_fix { 1 } else { 0 });
+            diff.v[i].overflowing_sub((mask & CORRECTION[i]) + u64::from(borrow_fix));
         result.v[i] = dif_bor.0;
         borrow_fix = dif_bor.1;
     }
@@ -91,8 +91,8 @@ const ONE: W6x64 = W6x64 {
 // Effectively a_norm = (a_mont * R^{-1}) mod N
 pub fn fe_to_norm(result: &mut [u64; 6], a: &W6x64) {
     let mut result_w6x64 = W6x64::default();
-    fe_mont_mul(&mut result_w6x64, &a, &ONE);
-    *result = result_w6x64.v
+    fe_mont_mul(&mut result_w6x64, a, &ONE);
+    *result = result_w6x64.v;
 }
 
 const N_PRIME: u64 = 0x89f3_fffc_fffc_fffd;
@@ -130,7 +130,7 @@ pub fn fe_mont_mul(result: &mut W6x64, a: &W6x64, b: &W6x64) {
     let mut borrow = 0_u64;
     for j in 0..6 {
         let (diff, borrow_t0) = temp[j + 6].overflowing_sub(N[j] + borrow);
-        dec[j] = diff as u64;
+        dec[j] = diff;
         borrow = u64::from(borrow_t0);
     }
 
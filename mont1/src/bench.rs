@@ -43,8 +43,8 @@ const EXPECTED_STR: &str = "169d18ab74c03e6199a9ec1869d2a2a0d53be1749c6acd502831
 
 // Montgomery addition x1000 written in Rust
 fn add_rust(x: &W6x64, y: &W6x64, expected: &W6x64) {
-    let mut xx = x.clone();
-    let mut yy = y.clone();
+    let mut xx = *x;
+    let mut yy = *y;
     let mut result = W6x64::default();
     for _i in 0..1_000 {
         fe_add(&mut result, &xx, &yy);
@@ -56,8 +56,8 @@ fn add_rust(x: &W6x64, y: &W6x64, expected: &W6x64) {
 
 // Montgomery subtraction x1000 written in Rust
 fn sub_rust(x: &W6x64, y: &W6x64, expected: &W6x64) {
-    let mut xx = x.clone();
-    let mut yy = y.clone();
+    let mut xx = *x;
+    let mut yy = *y;
     let mut result = W6x64::default();
     for _i in 0..1_000 {
         fe_sub(&mut result, &xx, &yy);
@@ -81,8 +81,8 @@ fn mul_big(x: &BigUint, y: &BigUint, expected: &BigUint, modulus: &BigUint) {
 
 // Montgomery multiplication x1000 written in Rust
 fn mul_rust(x: &W6x64, y: &W6x64, expected: &W6x64) {
-    let mut xx = x.clone();
-    let mut yy = y.clone();
+    let mut xx = *x;
+    let mut yy = *y;
     let mut result = W6x64::default();
     for _i in 0..1_000 {
         fe_mont_mul(&mut result, &xx, &yy);
@@ -1,3 +1,4 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)] // Tests always run with std
 #![deny(clippy::all)]
 #![deny(clippy::pedantic)]
 #![deny(clippy::cargo)]
@@ -1,74 +1,31 @@
 // Copyright 2021 Eric Schorn; Licensed under the 3-Clause BSD License.
 
-use std::arch::x86_64::{_addcarryx_u64, _mulx_u64, _subborrow_u64};
+use crate::field::{self, pow2_mod, BLS12_381_FP};
+#[cfg(all(feature = "intrinsics", target_arch = "x86_64"))]
+use core::arch::x86_64::{_addcarryx_u64, _mulx_u64, _subborrow_u64};
+use core::fmt;
+use core::ops::{BitAnd, BitOr, Not};
 
 #[rustfmt::skip]  // Save some vertical space
 // BLS12-381 field prime modulus N, least significant limb first
-const N: [u64; 6] = [
+pub(crate) const N: [u64; 6] = [
     0xb9fe_ffff_ffff_aaab, 0x1eab_fffe_b153_ffff, 0x6730_d2a0_f6b0_f624,
     0x6477_4b84_f385_12bf, 0x4b1b_a7b6_434b_acd7, 0x1a01_11ea_397f_e69a,
 ];
 
-#[allow(clippy::needless_range_loop)]
-// Assume properly reduced inputs and outputs
+// Assume properly reduced inputs and outputs; The generic code in field.rs does the work
 pub fn fe_add(result: &mut W6x64, a: &W6x64, b: &W6x64) {
-    let mut sum = W6x64::default(); // Initializes to zero
-    let mut carry = false;
-    for i in 0..6 {
-        let sum_car_a = a.v[i].overflowing_add(b.v[i]);
-        let sum_car_b = sum_car_a.0.overflowing_add(u64::from(carry));
-        sum.v[i] = sum_car_b.0;
-        carry = sum_car_a.1 | sum_car_b.1
-    }
-
-    let mut trial = W6x64::default();
-    let mut borrow = false;
-    for i in 0..6 {
-        // Note: a single overflowing_sub is sufficient because N[i]+borrow can never overflow
-        let dif_bor = sum.v[i].overflowing_sub(N[i] + u64::from(borrow));
-        trial.v[i] = dif_bor.0;
-        borrow = dif_bor.1;
-    }
-
-    let select_sum = u64::from(borrow).wrapping_neg();
-    for i in 0..6 {
-        result.v[i] = (!select_sum & trial.v[i]) | (select_sum & sum.v[i]);
-    }
+    BLS12_381_FP.add(&mut result.v, &a.v, &b.v);
 }
 
-#[rustfmt::skip]
-// 2**384 - N, least significant limb first
-const CORRECTION: [u64; 6] = [
-    0x4601_0000_0000_5555, 0xe154_0001_4eac_0000, 0x98cf_2d5f_094f_09db,
-    0x9b88_b47b_0c7a_ed40, 0xb4e4_5849_bcb4_5328, 0xe5fe_ee15_c680_1965,
-];
-
-#[allow(clippy::needless_range_loop)]
-// Assume properly reduced inputs and outputs
+// Assume properly reduced inputs and outputs; The generic code in field.rs does the work
 pub fn fe_sub(result: &mut W6x64, a: &W6x64, b: &W6x64) {
-    let mut diff = W6x64::default();
-    let mut borrow_sub = false;
-    for i in 0..6 {
-        let dif_bor_a = a.v[i].overflowing_sub(b.v[i]);
-        let dif_bor_b = dif_bor_a.0.overflowing_sub(u64::from(borrow_sub));
-        diff.v[i] = dif_bor_b.0;
-        borrow_sub = dif_bor_a.1 | dif_bor_b.1
-    }
-
-    let mask = u64::from(borrow_sub).wrapping_neg();
-    let mut borrow_fix = false;
-    for i in 0..6 {
-        let dif_bor =
-            // Note: a single overflowing_sub is sufficient because value+borrow can never overflow
-            diff.v[i].overflowing_sub((mask & CORRECTION[i]) + u64::from(borrow_fix));
-        result.v[i] = dif_bor.0;
-        borrow_fix = dif_bor.1;
-    }
+    BLS12_381_FP.sub(&mut result.v, &a.v, &b.v);
 }
 
 #[rustfmt::skip]
 // R^2 mod N, least significant limb first
-const R_SQUARED: W6x64 = W6x64 {
+pub(crate) const R_SQUARED: W6x64 = W6x64 {
     v: [0xf4df_1f34_1c34_1746, 0x0a76_e6a6_09d1_04f1, 0x8de5_476c_4c95_b6d5,
         0x67eb_88a9_939d_83c0, 0x9a79_3e85_b519_952d, 0x1198_8fe5_92ca_e3aa]
 };
@@ -76,7 +33,7 @@ const R_SQUARED: W6x64 = W6x64 {
 // Effectively a_mont = (a_norm * R) mod N
 pub fn fe_to_mont(result: &mut W6x64, a: &[u64; 6]) {
     let a_w6x64 = W6x64 { v: *a };
-    fe_mont_mul(&mut *result, &a_w6x64, &R_SQUARED);
+    fe_mont_mul_portable(&mut *result, &a_w6x64, &R_SQUARED);
 }
 
 #[rustfmt::skip]
@@ -89,8 +46,8 @@ const ONE: W6x64 = W6x64 {
 // Effectively a_norm = (a_mont * R^{-1}) mod N
 pub fn fe_to_norm(result: &mut [u64; 6], a: &W6x64) {
     let mut result_w6x64 = W6x64::default();
-    fe_mont_mul(&mut result_w6x64, &a, &ONE);
-    *result = result_w6x64.v
+    fe_mont_mul_portable(&mut result_w6x64, a, &ONE);
+    *result = result_w6x64.v;
 }
 
 #[derive(Default, Clone, Copy, Debug, PartialEq)] // Non constant-time Eq
@@ -99,45 +56,149 @@ pub struct W6x64 {
     pub v: [u64; 6], // From least significant limb [0] to most significant [5]
 }
 
-const N_PRIME: u64 = 0x89f3_fffc_fffc_fffd; // See constant.py
+// Constant-time boolean held as a mask of all zeros (false) or all ones (true)
+#[derive(Clone, Copy, Debug)]
+pub struct Choice(u64);
+
+impl Choice {
+    // Only the least significant bit of bit is considered; The volatile read hides the mask from
+    // the optimizer so it cannot turn mask arithmetic back into branches, without a function call
+    #[inline]
+    #[must_use]
+    pub fn from_bit(bit: u64) -> Self {
+        let mask = (bit & 1).wrapping_neg();
+        Choice(unsafe { core::ptr::read_volatile(&mask) })
+    }
+
+    // All zeros or all ones, ready for use in masking
+    #[must_use]
+    pub fn mask(self) -> u64 {
+        self.0
+    }
+}
+
+// Leaves constant-time territory; only for results that are public anyway
+impl From<Choice> for bool {
+    fn from(choice: Choice) -> bool {
+        choice.0 != 0
+    }
+}
+
+impl BitAnd for Choice {
+    type Output = Choice;
+    fn bitand(self, rhs: Choice) -> Choice {
+        Choice(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for Choice {
+    type Output = Choice;
+    fn bitor(self, rhs: Choice) -> Choice {
+        Choice(self.0 | rhs.0)
+    }
+}
+
+impl Not for Choice {
+    type Output = Choice;
+    fn not(self) -> Choice {
+        Choice(!self.0)
+    }
+}
+
+impl W6x64 {
+    // Constant-time equality across all limbs
+    #[must_use]
+    pub fn ct_eq(&self, other: &Self) -> Choice {
+        field::ct_eq(&self.v, &other.v)
+    }
+
+    // Constant-time test for all limbs being zero
+    #[must_use]
+    pub fn ct_is_zero(&self) -> Choice {
+        self.ct_eq(&W6x64::default())
+    }
+
+    // Returns b when choice is true, otherwise a
+    #[must_use]
+    pub fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        W6x64 { v: field::conditional_select(&a.v, &b.v, choice) }
+    }
+
+    // Swaps a and b when choice is true, otherwise leaves both untouched
+    pub fn conditional_swap(a: &mut Self, b: &mut Self, choice: Choice) {
+        for i in 0..6 {
+            let t = choice.0 & (a.v[i] ^ b.v[i]);
+            a.v[i] ^= t;
+            b.v[i] ^= t;
+        }
+    }
+}
+
+pub(crate) const N_PRIME: u64 = BLS12_381_FP.n_prime; // -N^{-1} mod 2^64; See constant.py
+
+// Effectively result_mont = (a_mont * b_mont * R^{-1}) mod N; Assume properly reduced input/output;
+// Always the portable code in field.rs, whichever backend dispatch.rs has selected
+pub fn fe_mont_mul_portable(result: &mut W6x64, a: &W6x64, b: &W6x64) {
+    BLS12_381_FP.mont_mul_portable(&mut result.v, &a.v, &b.v);
+}
 
 #[allow(clippy::cast_possible_truncation)]
-// Effectively result_mont = (a_mont * b_mont * R^{-1}) mod N; Assume properly reduced input/output
-pub fn fe_mont_mul(result: &mut W6x64, a: &W6x64, b: &W6x64) {
+// Effectively result_mont = (a_mont * a_mont * R^{-1}) mod N; Assume properly reduced input/output
+pub fn fe_mont_sqr_portable(result: &mut W6x64, a: &W6x64) {
     let mut temp = [0_u64; 12];
 
-    for i in 0..6 {
+    // Each cross product a[i]*a[j] with i < j appears twice, so accumulate it once...
+    for i in 0..5 {
         let mut carry = 0_u64;
-        for j in 0..6 {
-            let hilo = u128::from(a.v[j]) * u128::from(b.v[i])
+        for j in (i + 1)..6 {
+            let hilo = u128::from(a.v[i]) * u128::from(a.v[j])
                 + u128::from(temp[i + j])
-                + u128::from(carry); // Note (2^64-1)*(2^64-1)+2*(2^64-1) = 2^128-1
+                + u128::from(carry);
             temp[i + j] = hilo as u64;
             carry = (hilo >> 64) as u64;
         }
-        temp[i + 6] = temp[i + 6].wrapping_add(carry);
+        temp[i + 6] = carry;
+    }
 
-        let m: u64 = temp[i].wrapping_mul(N_PRIME);
+    // ...then double the lot and fold in the squares a[i]*a[i] along the diagonal
+    let mut carry = 0_u64;
+    for i in 0..6 {
+        let square = u128::from(a.v[i]) * u128::from(a.v[i]);
+        let lo = (u128::from(temp[2 * i]) << 1) + u128::from(square as u64) + u128::from(carry);
+        let hi =
+            (u128::from(temp[2 * i + 1]) << 1) + u128::from((square >> 64) as u64) + (lo >> 64);
+        temp[2 * i] = lo as u64;
+        temp[2 * i + 1] = hi as u64;
+        carry = (hi >> 64) as u64;
+    }
 
+    // Montgomery reduction of the 12-limb square, one limb at a time
+    let (n, n_prime) = (BLS12_381_FP.n, BLS12_381_FP.n_prime);
+    let mut carry_hi = false;
+    for i in 0..6 {
+        let m: u64 = temp[i].wrapping_mul(n_prime);
         let mut carry = 0_u64;
         for j in 0..6 {
             let hilo =
-                u128::from(m) * u128::from(N[j]) + u128::from(temp[i + j]) + u128::from(carry);
+                u128::from(m) * u128::from(n[j]) + u128::from(temp[i + j]) + u128::from(carry);
             temp[i + j] = hilo as u64;
             carry = (hilo >> 64) as u64;
         }
-        temp[i + 6] = temp[i + 6].wrapping_add(carry);
+        let (sum0, carry0) = temp[i + 6].overflowing_add(carry);
+        let (sum1, carry1) = sum0.overflowing_add(u64::from(carry_hi));
+        temp[i + 6] = sum1;
+        carry_hi = carry0 | carry1;
     }
 
     let mut dec = [0_u64; 6];
     let mut borrow = false;
     for j in 0..6 {
-        let (diff, borrow_tmp) = temp[j + 6].overflowing_sub(N[j] + u64::from(borrow));
-        dec[j] = diff as u64;
+        let (diff, borrow_tmp) = temp[j + 6].overflowing_sub(n[j] + u64::from(borrow));
+        dec[j] = diff;
         borrow = borrow_tmp;
     }
 
-    let select_temp = u64::from(borrow).wrapping_neg();
+    let select_temp = Choice::from_bit(u64::from(borrow)).mask();
     for j in 0..6 {
         result.v[j] = (select_temp & temp[j + 6]) | (!select_temp & dec[j]);
     }
@@ -180,7 +241,12 @@ macro_rules! mulx {
 }
 
 // Expected perf FOM = 12m + 6a + 13a/2 + 5*(12m + 13a/2) + 20 = 137/4.2GHz = 32.6ns; Act = 34nS
-#[allow(clippy::similar_names, clippy::shadow_unrelated, unused_parens)]
+#[allow(
+    clippy::similar_names,
+    clippy::shadow_unrelated,
+    clippy::cast_possible_truncation,
+    unused_parens
+)]
 pub fn fe_mont_mul_raw(result: &mut W6x64, a: &W6x64, b: &W6x64) {
     let (mut r10, mut r11, mut r12, mut r13, mut r14, mut r15);
 
@@ -286,17 +352,12 @@ pub fn fe_mont_mul_raw(result: &mut W6x64, a: &W6x64, b: &W6x64) {
     full_sub!(bor3, r14, (N[4]), (result.v[4]), bor4);
     full_sub!(bor4, r15, (N[5]), (result.v[5]), bor5);
 
-    // Mimic CMOV, but this is not constant-time!!
-    if bor5 {
-        result.v[0] = r10;
-        result.v[1] = r11;
-        result.v[2] = r12;
-        result.v[3] = r13;
-        result.v[4] = r14;
-        result.v[5] = r15;
-    }
+    // Mimic CMOV with a mask the optimizer cannot see through, so no branch reappears
+    let unreduced = W6x64 { v: [r10, r11, r12, r13, r14, r15] };
+    *result = W6x64::conditional_select(result, &unreduced, Choice::from_bit(u64::from(bor5)));
 }
 
+#[cfg(all(feature = "intrinsics", target_arch = "x86_64"))]
 #[allow(clippy::similar_names, clippy::too_many_lines)]
 pub fn fe_mont_mul_intrinsics(result: &mut W6x64, a: &W6x64, b: &W6x64) {
     unsafe {
@@ -346,10 +407,10 @@ pub fn fe_mont_mul_intrinsics(result: &mut W6x64, a: &W6x64, b: &W6x64) {
             let mut r16 = 0;
             rax = _mulx_u64(a.v[0], b.v[i], &mut rbx);
             let tmp3 = r10.overflowing_add(rax);
-            let cf2_a = tmp3.1 as u8;
+            let cf2_a = u8::from(tmp3.1);
             r10 = tmp3.0;
             let tmp4 = r11.overflowing_add(rbx);
-            let of2_a = tmp4.1 as u8;
+            let of2_a = u8::from(tmp4.1);
             r11 = tmp4.0;
             rax = _mulx_u64(a.v[1], b.v[i], &mut rbx);
             let cf2_b = _addcarryx_u64(cf2_a, r11, rax, &mut r11);
@@ -402,14 +463,469 @@ pub fn fe_mont_mul_intrinsics(result: &mut W6x64, a: &W6x64, b: &W6x64) {
         let bor4 = _subborrow_u64(bor3, r14, N[4], &mut result.v[4]);
         let bor5 = _subborrow_u64(bor4, r15, N[5], &mut result.v[5]);
 
-        // Mimic CMOV, but this is not constant-time!!
-        if bor5 != 0 {
-            result.v[0] = r10;
-            result.v[1] = r11;
-            result.v[2] = r12;
-            result.v[3] = r13;
-            result.v[4] = r14;
-            result.v[5] = r15;
+        // Mimic CMOV with a mask the optimizer cannot see through, so no branch reappears
+        let unreduced = W6x64 { v: [r10, r11, r12, r13, r14, r15] };
+        *result = W6x64::conditional_select(result, &unreduced, Choice::from_bit(u64::from(bor5)));
+    }
+}
+
+// R^3 mod N, least significant limb first
+const R_CUBED: W6x64 = W6x64 { v: pow2_mod(N, 3) };
+
+// Effectively a = (a + (mask & N)) / 2 across all 6 limbs; the sum cannot exceed 382 bits
+fn half_plus_n(a: &mut [u64; 6], mask: u64) {
+    let mut carry = false;
+    for (limb, n) in a.iter_mut().zip(BLS12_381_FP.n.iter()) {
+        let (sum0, carry0) = limb.overflowing_add(mask & n);
+        let (sum1, carry1) = sum0.overflowing_add(u64::from(carry));
+        *limb = sum1;
+        carry = carry0 | carry1;
+    }
+    for i in 0..5 {
+        a[i] = (a[i] >> 1) | (a[i + 1] << 63);
+    }
+    a[5] >>= 1;
+}
+
+// Iteration count covering the combined 381+381 bit lengths of the operand and N
+const INV_ITERATIONS: usize = 2 * 384;
+
+// Effectively result_mont = (a_mont^{-1}) mod N via a constant-time binary extended GCD;
+// Assume properly reduced input/output; Returns true (with a zero result) when a is zero
+#[allow(clippy::many_single_char_names)]
+pub fn fe_inv(result: &mut W6x64, a: &W6x64) -> Choice {
+    // Invariants: x = u * a_mont and y = v * a_mont (mod N), with y always odd
+    let mut x = *a;
+    let mut y = W6x64 { v: BLS12_381_FP.n };
+    let mut u = W6x64 { v: [1, 0, 0, 0, 0, 0] };
+    let mut v = W6x64::default();
+
+    for _i in 0..INV_ITERATIONS {
+        // When x is odd, ensure x >= y (swapping if needed), then subtract y from x
+        let odd = Choice::from_bit(x.v[0]);
+        let mut borrow = false;
+        for i in 0..6 {
+            let (diff0, borrow0) = x.v[i].overflowing_sub(y.v[i]);
+            let (_, borrow1) = diff0.overflowing_sub(u64::from(borrow));
+            borrow = borrow0 | borrow1;
+        }
+        let swap = odd & Choice::from_bit(u64::from(borrow));
+        W6x64::conditional_swap(&mut x, &mut y, swap);
+        W6x64::conditional_swap(&mut u, &mut v, swap);
+
+        let mut borrow = false;
+        for i in 0..6 {
+            let (diff0, borrow0) = x.v[i].overflowing_sub(odd.mask() & y.v[i]);
+            let (diff1, borrow1) = diff0.overflowing_sub(u64::from(borrow));
+            x.v[i] = diff1;
+            borrow = borrow0 | borrow1;
+        }
+        let v_masked = W6x64::conditional_select(&W6x64::default(), &v, odd);
+        let u_prev = u;
+        fe_sub(&mut u, &u_prev, &v_masked);
+
+        // Now x is even, so halve it along with u (mod N)
+        for i in 0..5 {
+            x.v[i] = (x.v[i] >> 1) | (x.v[i + 1] << 63);
+        }
+        x.v[5] >>= 1;
+        let u_odd = Choice::from_bit(u.v[0]);
+        half_plus_n(&mut u.v, u_odd.mask());
+    }
+
+    // v = (a * R)^{-1} = a^{-1} * R^{-1}, so multiply by R^3 to land at a^{-1} * R
+    fe_mont_mul_portable(result, &v, &R_CUBED);
+    a.ct_is_zero()
+}
+
+#[rustfmt::skip]
+// R mod N (i.e. one in Montgomery form), least significant limb first
+pub(crate) const R_MOD_N: W6x64 = W6x64 {
+    v: [0x7609_0000_0002_fffd, 0xebf4_000b_c40c_0002, 0x5f48_9857_53c7_58ba,
+        0x77ce_5853_7052_5745, 0x5c07_1a97_a256_ec6d, 0x15f6_5ec3_fa80_e493]
+};
+
+// Width of the fixed window in fe_pow, with a table of 2^POW_WINDOW entries
+const POW_WINDOW: usize = 4;
+
+// Copy table[index] into result while touching every entry, so the index stays secret
+fn ct_lookup(result: &mut W6x64, table: &[W6x64], index: u64) {
+    *result = W6x64::default();
+    for (i, entry) in (0_u64..).zip(table.iter()) {
+        let diff = i ^ index;
+        let is_index = Choice::from_bit(((diff | diff.wrapping_neg()) >> 63) ^ 1);
+        *result = W6x64::conditional_select(result, entry, is_index);
+    }
+}
+
+// Effectively result_mont = a_mont^exponent mod N with a fixed 4-bit window; Exponent limbs are
+// least significant first, and only their count (not their value) influences the timing
+pub fn fe_pow(result: &mut W6x64, a: &W6x64, exponent: &[u64]) {
+    let mut table = [R_MOD_N; 1 << POW_WINDOW];
+    for i in 1..(1 << POW_WINDOW) {
+        let previous = table[i - 1];
+        fe_mont_mul_portable(&mut table[i], &previous, a);
+    }
+
+    let mut acc = R_MOD_N;
+    let mut temp = W6x64::default();
+    let mut entry = W6x64::default();
+    for limb in exponent.iter().rev() {
+        for window in (0..(64 / POW_WINDOW)).rev() {
+            for _j in 0..POW_WINDOW {
+                fe_mont_sqr_portable(&mut temp, &acc);
+                acc = temp;
+            }
+            let index = (limb >> (window * POW_WINDOW)) & ((1 << POW_WINDOW) - 1);
+            ct_lookup(&mut entry, &table, index);
+            fe_mont_mul_portable(&mut temp, &acc, &entry);
+            acc = temp;
+        }
+    }
+    *result = acc;
+}
+
+// Effectively result_mont = a_mont^exponent mod N by square-and-multiply; Not constant-time!!
+// Intended for public exponents only; Exponent limbs are least significant first
+pub fn fe_pow_vartime(result: &mut W6x64, a: &W6x64, exponent: &[u64]) {
+    let mut acc = R_MOD_N;
+    let mut temp = W6x64::default();
+    let mut started = false;
+    for limb in exponent.iter().rev() {
+        for bit in (0..64).rev() {
+            if started {
+                fe_mont_sqr_portable(&mut temp, &acc);
+                acc = temp;
+            }
+            if (limb >> bit) & 1 == 1 {
+                fe_mont_mul_portable(&mut temp, &acc, a);
+                acc = temp;
+                started = true;
+            }
+        }
+    }
+    *result = acc;
+}
+
+// Effectively result_mont = a_mont^e mod N for the fixed addition chain describing e; Each step
+// is (squarings, index k of the odd power a^(2k+1) to multiply in afterwards), starting from one
+pub fn fe_pow_chain(result: &mut W6x64, a: &W6x64, chain: &[(u8, u8)]) {
+    // Odd powers a^1, a^3, ..., a^31
+    let mut odd_powers = [*a; 16];
+    let mut a_squared = W6x64::default();
+    fe_mont_sqr_portable(&mut a_squared, a);
+    for i in 1..16 {
+        let previous = odd_powers[i - 1];
+        fe_mont_mul_portable(&mut odd_powers[i], &previous, &a_squared);
+    }
+
+    // The chain is public, so the sequence of operations is independent of a
+    let mut acc = R_MOD_N;
+    let mut temp = W6x64::default();
+    for &(squarings, index) in chain {
+        for _j in 0..squarings {
+            fe_mont_sqr_portable(&mut temp, &acc);
+            acc = temp;
+        }
+        fe_mont_mul_portable(&mut temp, &acc, &odd_powers[index as usize]);
+        acc = temp;
+    }
+    *result = acc;
+}
+
+#[rustfmt::skip]
+// Sliding-window (width 5) addition chain for (N+1)/4 in the fe_pow_chain step format
+const SQRT_CHAIN: [(u8, u8); 67] = [
+    (0, 6),
+    (13, 8), (7, 7), (4, 2), (6, 3), (7, 11), (5, 15), (5, 12), (3, 2), (6, 6), (6, 4), (3, 1),
+    (8, 13), (3, 2), (6, 7), (6, 13), (3, 0), (8, 6), (7, 11), (5, 5), (6, 6), (6, 14), (4, 4),
+    (8, 14), (4, 6), (7, 11), (9, 9), (5, 12), (2, 1), (7, 2), (7, 4), (6, 11), (5, 14), (5, 9),
+    (5, 9), (8, 6), (7, 10), (9, 7), (5, 6), (3, 1), (8, 7), (3, 1), (7, 4), (9, 7), (6, 10),
+    (6, 15), (5, 15), (5, 15), (4, 6), (3, 1), (8, 10), (7, 15), (5, 15), (5, 15), (4, 7), (4, 3),
+    (7, 15), (5, 14), (5, 15), (5, 15), (5, 15), (5, 15), (5, 15), (5, 15), (4, 6), (6, 10), (5, 5),
+];
+
+// Effectively result_mont = a_mont^((N+1)/4) mod N, a square root when one exists since N = 3 mod 4;
+// Assume properly reduced input/output; Returns a false Choice for a non-residue, with the result
+// then sqrt(-a)
+pub fn fe_sqrt(result: &mut W6x64, a: &W6x64) -> Choice {
+    fe_pow_chain(result, a, &SQRT_CHAIN);
+
+    // Confirm result^2 == a without branching on the limbs
+    let mut temp = W6x64::default();
+    fe_mont_sqr_portable(&mut temp, result);
+    temp.ct_eq(a)
+}
+
+// Constant-time quadratic residue test (zero counts as a square); see fe_jacobi_vartime
+#[must_use]
+pub fn fe_is_square(a: &W6x64) -> Choice {
+    let mut root = W6x64::default();
+    fe_sqrt(&mut root, a)
+}
+
+// Returns true when x < y, comparing from the most significant limb down (variable time)
+fn lt_6x64_vartime(x: &[u64; 6], y: &[u64; 6]) -> bool {
+    x.iter().rev().cmp(y.iter().rev()) == core::cmp::Ordering::Less
+}
+
+// Legendre symbol (a/N) as 1, -1 or 0 via the binary Jacobi algorithm; Not constant-time!!
+// Since R = 2^384 is an even power of two, (a_mont/N) equals (a_norm/N)
+#[must_use]
+pub fn fe_jacobi_vartime(a: &W6x64) -> i8 {
+    let mut x = a.v;
+    let mut y = N;
+    let mut sign = 1_i8;
+
+    while x.iter().any(|&limb| limb != 0) {
+        // Strip factors of two from x (up to a full limb at a time), flipping the sign for
+        // each odd count when y = 3 or 5 mod 8
+        let zeros = x[0].trailing_zeros();
+        if zeros == 64 {
+            x.copy_within(1..6, 0);
+            x[5] = 0;
+            continue;
+        }
+        if zeros > 0 {
+            for i in 0..5 {
+                x[i] = (x[i] >> zeros) | (x[i + 1] << (64 - zeros));
+            }
+            x[5] >>= zeros;
+            if (zeros & 1 == 1) && (y[0] & 7 == 3 || y[0] & 7 == 5) {
+                sign = -sign;
+            }
+        }
+
+        // Both odd now; apply quadratic reciprocity when swapping so that x >= y
+        if lt_6x64_vartime(&x, &y) {
+            core::mem::swap(&mut x, &mut y);
+            if x[0] & 3 == 3 && y[0] & 3 == 3 {
+                sign = -sign;
+            }
+        }
+
+        // (x/y) = ((x-y)/y)
+        let mut borrow = false;
+        for i in 0..6 {
+            let (diff0, borrow0) = x[i].overflowing_sub(y[i]);
+            let (diff1, borrow1) = diff0.overflowing_sub(u64::from(borrow));
+            x[i] = diff1;
+            borrow = borrow0 | borrow1;
+        }
+    }
+
+    if y == [1, 0, 0, 0, 0, 0] {
+        sign
+    } else {
+        0
+    }
+}
+
+// Lazy reduction: N < 2^381 leaves three spare bits in the top limb, so values may run above N
+// between operations. The valid ranges are spelled out per function below; fe_mont_mul_portable
+// and fe_to_norm also accept any a_mont, b_mont with a * b < N * R (about 9.8 N^2), e.g. [0, 2N)
+// inputs, and always return a fully reduced [0, N) result.
+
+// 2N, least significant limb first
+const N_TWICE: [u64; 6] = shl_one(N);
+
+// Effectively 2 * n for n < 2^383, shifting each limb's top bit into the next
+const fn shl_one(n: [u64; 6]) -> [u64; 6] {
+    let mut result = [n[0] << 1, 0, 0, 0, 0, 0];
+    let mut i = 1;
+    while i < 6 {
+        result[i] = (n[i] << 1) | (n[i - 1] >> 63);
+        i += 1;
+    }
+    result
+}
+
+// Effectively result = a + b with no reduction at all; Inputs [0, 2N) give a result in [0, 4N)
+pub fn fe_add_lazy(result: &mut W6x64, a: &W6x64, b: &W6x64) {
+    let mut carry = false;
+    for i in 0..6 {
+        let (sum0, carry0) = a.v[i].overflowing_add(b.v[i]);
+        let (sum1, carry1) = sum0.overflowing_add(u64::from(carry));
+        result.v[i] = sum1;
+        carry = carry0 | carry1;
+    }
+}
+
+#[allow(clippy::needless_range_loop)]
+// Effectively result = a + 2N - b with no reduction; Inputs [0, 2N) give a result in (0, 4N)
+pub fn fe_sub_lazy(result: &mut W6x64, a: &W6x64, b: &W6x64) {
+    let mut carry = false;
+    let mut borrow = false;
+    for i in 0..6 {
+        let (sum0, carry0) = a.v[i].overflowing_add(N_TWICE[i]);
+        let (sum1, carry1) = sum0.overflowing_add(u64::from(carry));
+        let (diff0, borrow0) = sum1.overflowing_sub(b.v[i]);
+        let (diff1, borrow1) = diff0.overflowing_sub(u64::from(borrow));
+        result.v[i] = diff1;
+        carry = carry0 | carry1;
+        borrow = borrow0 | borrow1;
+    }
+}
+
+#[allow(clippy::needless_range_loop)]
+// Subtract modulus from a when a >= modulus, in constant time
+fn sub_if_above(a: &mut W6x64, modulus: &[u64; 6]) {
+    let mut trial = W6x64::default();
+    let mut borrow = false;
+    for i in 0..6 {
+        let (diff0, borrow0) = a.v[i].overflowing_sub(modulus[i]);
+        let (diff1, borrow1) = diff0.overflowing_sub(u64::from(borrow));
+        trial.v[i] = diff1;
+        borrow = borrow0 | borrow1;
+    }
+    *a = W6x64::conditional_select(&trial, a, Choice::from_bit(u64::from(borrow)));
+}
+
+// Effectively result = a mod N for any a in [0, 4N), via masked subtractions of 2N then N
+pub fn fe_reduce(result: &mut W6x64, a: &W6x64) {
+    *result = *a;
+    sub_if_above(result, &N_TWICE);
+    sub_if_above(result, &N);
+}
+
+#[allow(clippy::cast_possible_truncation)]
+// Effectively result_mont = (a_mont * b_mont * R^{-1}) mod N, but without the final subtraction;
+// Valid whenever a * b < N * R, e.g. a in [0, 4N) with b in [0, 2N); The result is in [0, 2N)
+pub fn fe_mont_mul_lazy(result: &mut W6x64, a: &W6x64, b: &W6x64) {
+    let (n, n_prime) = (BLS12_381_FP.n, BLS12_381_FP.n_prime);
+    let mut temp = [0_u64; 12];
+
+    for i in 0..6 {
+        let mut carry = 0_u64;
+        for j in 0..6 {
+            let hilo = u128::from(a.v[j]) * u128::from(b.v[i])
+                + u128::from(temp[i + j])
+                + u128::from(carry);
+            temp[i + j] = hilo as u64;
+            carry = (hilo >> 64) as u64;
+        }
+        temp[i + 6] = temp[i + 6].wrapping_add(carry);
+
+        let m: u64 = temp[i].wrapping_mul(n_prime);
+
+        let mut carry = 0_u64;
+        for j in 0..6 {
+            let hilo =
+                u128::from(m) * u128::from(n[j]) + u128::from(temp[i + j]) + u128::from(carry);
+            temp[i + j] = hilo as u64;
+            carry = (hilo >> 64) as u64;
+        }
+        temp[i + 6] = temp[i + 6].wrapping_add(carry);
+    }
+
+    // (a * b + m * N) / R < N + (a * b) / R < 2N, so no subtraction is needed
+    result.v.copy_from_slice(&temp[6..12]);
+}
+
+// Invert every element in place with Montgomery's simultaneous inversion trick, costing a single
+// fe_inv plus three multiplications per element; Zero elements are left as zero; scratch holds the
+// running products, so this needs no allocation; Panics unless both slices have the same length
+#[allow(clippy::missing_panics_doc)]
+pub fn fe_batch_inv(values: &mut [W6x64], scratch: &mut [W6x64]) {
+    assert_eq!(values.len(), scratch.len());
+    let mut acc = R_MOD_N;
+    let mut temp = W6x64::default();
+    for (value, prefix) in values.iter().zip(scratch.iter_mut()) {
+        *prefix = acc;
+        // Zeros are stood in for by one so they cannot wipe out the running product
+        let nonzero = W6x64::conditional_select(value, &R_MOD_N, value.ct_is_zero());
+        fe_mont_mul_portable(&mut temp, &acc, &nonzero);
+        acc = temp;
+    }
+
+    let mut acc_inv = W6x64::default();
+    let _ = fe_inv(&mut acc_inv, &acc); // acc is never zero
+    for (value, prefix) in values.iter_mut().zip(scratch.iter()).rev() {
+        let is_zero = value.ct_is_zero();
+        let nonzero = W6x64::conditional_select(value, &R_MOD_N, is_zero);
+        let mut inverse = W6x64::default();
+        fe_mont_mul_portable(&mut inverse, &acc_inv, prefix);
+        fe_mont_mul_portable(&mut temp, &acc_inv, &nonzero);
+        acc_inv = temp;
+        *value = W6x64::conditional_select(&inverse, &W6x64::default(), is_zero);
+    }
+}
+
+// Length in bytes of a canonical field element encoding
+pub const FE_BYTES: usize = 48;
+
+// Reasons an untrusted encoding is refused
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FieldError {
+    NotCanonical, // The encoded value is N or larger
+}
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FieldError::NotCanonical => write!(f, "encoded field element is not less than N"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FieldError {}
+
+#[allow(clippy::missing_errors_doc)] // The only error is FieldError::NotCanonical
+impl W6x64 {
+    // Decode 48 big-endian bytes holding a canonical value into Montgomery form
+    pub fn from_bytes_be(bytes: &[u8; FE_BYTES]) -> Result<Self, FieldError> {
+        let mut le_bytes = *bytes;
+        le_bytes.reverse();
+        W6x64::from_bytes_le(&le_bytes)
+    }
+
+    // Decode 48 little-endian bytes holding a canonical value into Montgomery form
+    pub fn from_bytes_le(bytes: &[u8; FE_BYTES]) -> Result<Self, FieldError> {
+        let mut norm = [0_u64; 6];
+        for (limb, chunk) in norm.iter_mut().zip(bytes.chunks(8)) {
+            let mut limb_bytes = [0_u8; 8];
+            limb_bytes.copy_from_slice(chunk);
+            *limb = u64::from_le_bytes(limb_bytes);
+        }
+        W6x64::from_norm(&norm)
+    }
+
+    // Convert a canonical (normal form) value into Montgomery form, refusing anything >= N
+    pub fn from_norm(norm: &[u64; 6]) -> Result<Self, FieldError> {
+        // A final borrow from norm - N is what marks norm < N
+        let mut borrow = false;
+        for i in 0..6 {
+            let (diff0, borrow0) = norm[i].overflowing_sub(N[i]);
+            let (_, borrow1) = diff0.overflowing_sub(u64::from(borrow));
+            borrow = borrow0 | borrow1;
+        }
+        if !borrow {
+            return Err(FieldError::NotCanonical);
+        }
+
+        let mut result = W6x64::default();
+        fe_to_mont(&mut result, norm);
+        Ok(result)
+    }
+
+    // Encode from Montgomery form as 48 big-endian bytes of the canonical value
+    #[must_use]
+    pub fn to_bytes_be(&self) -> [u8; FE_BYTES] {
+        let mut bytes = self.to_bytes_le();
+        bytes.reverse();
+        bytes
+    }
+
+    // Encode from Montgomery form as 48 little-endian bytes of the canonical value
+    #[must_use]
+    pub fn to_bytes_le(&self) -> [u8; FE_BYTES] {
+        let mut norm = [0_u64; 6];
+        fe_to_norm(&mut norm, self);
+        let mut bytes = [0_u8; FE_BYTES];
+        for (chunk, limb) in bytes.chunks_mut(8).zip(norm.iter()) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
         }
+        bytes
     }
 }
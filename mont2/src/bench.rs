@@ -1,8 +1,17 @@
 // Copyright 2021 Eric Schorn; Licensed under the 3-Clause BSD License.
 
 use criterion::{criterion_group, criterion_main, Criterion};
-use mont2::arith::{fe_add, fe_mont_mul, fe_mont_mul_intrinsics, fe_mont_mul_raw, fe_sub, W6x64};
-use mont2::fe_mont_mul_asm;
+use mont2::arith::{
+    fe_add, fe_mont_mul_intrinsics, fe_mont_mul_portable, fe_mont_mul_raw, fe_mont_sqr_portable,
+    fe_sub, W6x64,
+};
+use mont2::field::{MontField, BLS12_377_FQ, BLS12_377_FR, BN254_FQ, BN254_FR};
+use mont2::fp::Fp;
+use mont2::fp12::{Fp12, Fp12Compressed};
+use mont2::fp12_alt::Fp12Alt;
+use mont2::fp2::Fp2;
+use mont2::fp6::Fp6;
+use mont2::{fe_mont_mul_asm, fe_mont_sqr_asm};
 use num_bigint::BigUint;
 use num_traits::Num;
 use std::time::Duration;
@@ -45,6 +54,45 @@ const EXP_PROD: W6x64 = W6x64 {
         0x448683648418e8dd, 0xf3599187e803fc7e, 0x1118bd439ac24052],
 };
 
+#[rustfmt::skip]
+const EXP_SQR: W6x64 = W6x64 {
+    v: [0x577ff3ef10446e89, 0xf3390cb8f783e3ae, 0x1bfff56afeb8ffb9,
+        0x9e5cf346cc087729, 0xc6339bf7712e724d, 0xcdde8f03e6eb934],
+};
+
+// Inputs and expected results for 1000 iterations over the other curves' fields, taken from the
+// low limbs of X and Y reduced by each modulus
+#[rustfmt::skip]
+const BN254_FQ_XY_EXP: [[u64; 4]; 3] = [
+    [0xd2be_dfb6_b636_0569, 0x6629_b712_cd4c_8177, 0x7957_db31_1b5e_e194, 0x0c45_84ec_b503_e4af],
+    [0x040f_ea52_59fc_987a, 0x4153_c94a_888f_8c4e, 0x6a45_5f45_83b0_b73c, 0x08b2_d45a_34fb_8136],
+    [0xc393_5c18_6e6d_090e, 0x6328_903a_5859_4383, 0x8939_19d1_55a2_980f, 0x230e_4d15_b6c5_9583],
+];
+
+#[rustfmt::skip]
+const BN254_FR_XY_EXP: [[u64; 4]; 3] = [
+    [0xb3b9_39c2_5829_fa81, 0x235f_c036_882d_e967, 0x7957_db31_1b5e_e196, 0x0c45_84ec_b503_e4af],
+    [0xe50a_445d_fbf0_8d92, 0xfe89_d26e_4370_f43d, 0x6a45_5f45_83b0_b73d, 0x08b2_d45a_34fb_8136],
+    [0x6543_a7f0_520f_91e5, 0x01a9_a518_9270_a014, 0x08fa_6c6e_be35_9863, 0x1e78_2b69_b758_79b5],
+];
+
+#[rustfmt::skip]
+const BLS12_377_FQ_XY_EXP: [[u64; 6]; 3] = [
+    [0x15f2_5012_1829_fa7c, 0xf4c9_19f2_bf13_aba8, 0x440a_7e5d_9710_bb09,
+     0xe29d_152d_312c_b54e, 0xe2b7_b297_1796_55d4, 0x0039_3dcd_f5f1_72a0],
+    [0xea80_9aad_bbf0_8d94, 0x7142_b907_ca56_b681, 0x0d9f_b1c0_15a3_88b2,
+     0x95fe_5a3f_b7d7_dabf, 0x4754_dfc9_0323_f4b0, 0x00ba_59b0_33d4_3c4e],
+    [0xc976_91cc_6ac8_c6f2, 0x968b_7e66_2cb0_66ff, 0x79d5_2fa1_cbcd_2261,
+     0xb82d_612e_b38e_891b, 0x6496_401b_5287_7710, 0x013d_a27c_fc8f_31b5],
+];
+
+#[rustfmt::skip]
+const BLS12_377_FR_XY_EXP: [[u64; 4]; 3] = [
+    [0x5480_9012_1829_fa7a, 0xe9dc_4465_7f13_aba1, 0x32d9_a1bd_2aff_b2fb, 0x0079_63a7_99df_4aa0],
+    [0x8fe3_1aad_bbf0_8d8c, 0x1eb0_cd9c_0a56_b678, 0x847b_72ef_ef89_38a5, 0x0f92_1873_b403_8c7d],
+    [0x4fbc_64dc_a3d9_d041, 0xc2c0_dd0a_b05f_2068, 0xa837_2383_c98e_6d16, 0x02da_6fd0_5da2_4595],
+];
+
 lazy_static! { static ref EXPECTED: BigUint = BigUint::from_str_radix(
     "169d18ab74c03e6199a9ec1869d2a2a0d53be1749c6acd5028310a17f06383087d69cb203aa01ae0a73a546f5db98555",
     16).unwrap();
@@ -52,8 +100,8 @@ lazy_static! { static ref EXPECTED: BigUint = BigUint::from_str_radix(
 
 // Montgomery addition x1000 written in Rust
 fn add_rust(x: &W6x64, y: &W6x64, expected: &W6x64) {
-    let mut xx = x.clone();
-    let mut yy = y.clone();
+    let mut xx = *x;
+    let mut yy = *y;
     let mut result = W6x64::default();
     for _i in 0..1_000 {
         fe_add(&mut result, &xx, &yy);
@@ -65,8 +113,8 @@ fn add_rust(x: &W6x64, y: &W6x64, expected: &W6x64) {
 
 // Montgomery subtraction x1000 written in Rust
 fn sub_rust(x: &W6x64, y: &W6x64, expected: &W6x64) {
-    let mut xx = x.clone();
-    let mut yy = y.clone();
+    let mut xx = *x;
+    let mut yy = *y;
     let mut result = W6x64::default();
     for _i in 0..1_000 {
         fe_sub(&mut result, &xx, &yy);
@@ -100,13 +148,13 @@ fn mul_big(x: &BigUint, y: &BigUint, expected: &BigUint) {
     assert_eq!(&xx, expected)
 }
 
-// Montgomery multiplication x1000 written in Rust (mont1 blog)
+// Montgomery multiplication x1000 written in generic Rust (MontField, as in the mont1 blog)
 fn mul_rust(x: &W6x64, y: &W6x64, expected: &W6x64) {
-    let mut xx = x.clone();
-    let mut yy = y.clone();
+    let mut xx = *x;
+    let mut yy = *y;
     let mut result = W6x64::default();
     for _i in 0..1_000 {
-        fe_mont_mul(&mut result, &xx, &yy);
+        fe_mont_mul_portable(&mut result, &xx, &yy);
         yy = xx;
         xx = result;
     }
@@ -115,8 +163,8 @@ fn mul_rust(x: &W6x64, y: &W6x64, expected: &W6x64) {
 
 // Montgomery multiplication x1000 written in Assembly
 fn mul_asm(x: &W6x64, y: &W6x64, expected: &W6x64) {
-    let mut xx = x.clone();
-    let mut yy = y.clone();
+    let mut xx = *x;
+    let mut yy = *y;
     let mut result = W6x64::default();
     for _i in 0..1_000 {
         unsafe {
@@ -130,8 +178,8 @@ fn mul_asm(x: &W6x64, y: &W6x64, expected: &W6x64) {
 
 // Montgomery multiplication x1000 written in Rust with intrinsics
 fn mul_rust_intrinsics(x: &W6x64, y: &W6x64, expected: &W6x64) {
-    let mut xx = x.clone();
-    let mut yy = y.clone();
+    let mut xx = *x;
+    let mut yy = *y;
     let mut result = W6x64::default();
     for _i in 0..1_000 {
         fe_mont_mul_intrinsics(&mut result, &xx, &yy);
@@ -143,8 +191,8 @@ fn mul_rust_intrinsics(x: &W6x64, y: &W6x64, expected: &W6x64) {
 
 // Montgomery multiplication x1000 written in Rust (backported asm)
 fn mul_rust_raw(x: &W6x64, y: &W6x64, expected: &W6x64) {
-    let mut xx = x.clone();
-    let mut yy = y.clone();
+    let mut xx = *x;
+    let mut yy = *y;
     let mut result = W6x64::default();
     for _i in 0..1_000 {
         fe_mont_mul_raw(&mut result, &xx, &yy);
@@ -154,6 +202,110 @@ fn mul_rust_raw(x: &W6x64, y: &W6x64, expected: &W6x64) {
     assert_eq!(&result, expected);
 }
 
+// Montgomery squaring x1000 written in Rust
+fn sqr_rust(x: &W6x64, expected: &W6x64) {
+    let mut xx = *x;
+    let mut result = W6x64::default();
+    for _i in 0..1_000 {
+        fe_mont_sqr_portable(&mut result, &xx);
+        xx = result;
+    }
+    assert_eq!(&result, expected);
+}
+
+// Montgomery squaring x1000 written in Assembly
+fn sqr_asm(x: &W6x64, expected: &W6x64) {
+    let mut xx = *x;
+    let mut result = W6x64::default();
+    for _i in 0..1_000 {
+        unsafe {
+            fe_mont_sqr_asm(&mut result.v[0], &xx.v[0]);
+        }
+        xx = result;
+    }
+    assert_eq!(&result, expected);
+}
+
+// Montgomery multiplication x1000 with the generic MontField code
+fn mul_field<const LIMBS: usize>(field: &MontField<LIMBS>, xy_exp: &[[u64; LIMBS]; 3]) {
+    let mut xx = xy_exp[0];
+    let mut yy = xy_exp[1];
+    let mut result = [0_u64; LIMBS];
+    for _i in 0..1_000 {
+        field.mont_mul(&mut result, &xx, &yy);
+        yy = xx;
+        xx = result;
+    }
+    assert_eq!(result, xy_exp[2]);
+}
+
+// Arbitrary Fp2 and Fp12 values built from small integers, standing in for Miller loop data
+fn fp2_from_u64(x: u64) -> Fp2 {
+    Fp2::new(Fp::from_u64(x), Fp::from_u64(x ^ 0x5555_5555))
+}
+
+fn fp12_from_u64(x: u64) -> Fp12 {
+    let c0 = Fp6::new(fp2_from_u64(x), fp2_from_u64(x + 1), fp2_from_u64(x + 2));
+    Fp12::new(c0, Fp6::new(fp2_from_u64(x + 3), fp2_from_u64(x + 4), fp2_from_u64(x + 5)))
+}
+
+// Fp12 accumulator times the same line x1000, as a dense product or via the sparse routines
+fn mul_fp12_dense(f: &Fp12, line: &Fp12) -> Fp12 {
+    let mut acc = *f;
+    for _i in 0..1_000 {
+        acc *= line;
+    }
+    acc
+}
+
+fn mul_fp12_014(f: &Fp12, b0: &Fp2, b1: &Fp2, b4: &Fp2) -> Fp12 {
+    let mut acc = *f;
+    for _i in 0..1_000 {
+        acc = acc.mul_by_014(b0, b1, b4);
+    }
+    acc
+}
+
+fn mul_fp12_034(f: &Fp12, b0: &Fp2, b3: &Fp2, b4: &Fp2) -> Fp12 {
+    let mut acc = *f;
+    for _i in 0..1_000 {
+        acc = acc.mul_by_034(b0, b3, b4);
+    }
+    acc
+}
+
+// Repeated squaring x1000 of an element of the cyclotomic subgroup, generically, via Granger-Scott,
+// and in Karabina's compressed form with a single decompression at the end
+fn sqr_fp12(f: &Fp12) -> Fp12 {
+    (0..1_000).fold(*f, |acc, _| acc.square())
+}
+
+fn sqr_fp12_cyclotomic(f: &Fp12) -> Fp12 {
+    (0..1_000).fold(*f, |acc, _| acc.cyclotomic_square())
+}
+
+fn sqr_fp12_compressed(f: &Fp12) -> Fp12 {
+    (0..1_000).fold(f.compress(), |acc, _| acc.square()).decompress()
+}
+
+// Repeated multiplication x1000 (by a fixed element) in either tower
+fn mul_fp12(f: &Fp12, g: &Fp12) -> Fp12 {
+    (0..1_000).fold(*f, |acc, _| acc * g)
+}
+
+fn mul_fp12_alt(f: &Fp12Alt, g: &Fp12Alt) -> Fp12Alt {
+    (0..1_000).fold(*f, |acc, _| acc * g)
+}
+
+// Repeated squaring x1000 in the alternative tower, generically or via Granger-Scott
+fn sqr_fp12_alt(f: &Fp12Alt) -> Fp12Alt {
+    (0..1_000).fold(*f, |acc, _| acc.square())
+}
+
+fn sqr_fp12_alt_cyclotomic(f: &Fp12Alt) -> Fp12Alt {
+    (0..1_000).fold(*f, |acc, _| acc.cyclotomic_square())
+}
+
 // Harness for addition with inputs and expected result
 pub fn bench_add(c: &mut Criterion) {
     c.bench_function("1. Addition X 1000 iterations", |b| b.iter(|| add_rust(&X, &Y, &EXP_SUM)));
@@ -171,12 +323,12 @@ pub fn bench_mul_big(c: &mut Criterion) {
     let x = BigUint::from(u128::MAX);
     let y = BigUint::from(u64::MAX);
     c.bench_function("3. Multiplication by BigUint X 1000 iterations", |b| {
-        b.iter(|| mul_big(&x, &y, &(*EXPECTED)))
+        b.iter(|| mul_big(&x, &y, &EXPECTED))
     });
 }
 
 pub fn bench_mul_rust(c: &mut Criterion) {
-    c.bench_function("4. Multiplication in Rust (mont1 blog) X 1000 iterations", |b| {
+    c.bench_function("4. Multiplication in generic Rust X 1000 iterations", |b| {
         b.iter(|| mul_rust(&X, &Y, &EXP_PROD))
     });
 }
@@ -199,11 +351,135 @@ pub fn bench_mul_asm(c: &mut Criterion) {
     });
 }
 
-// Run all seven harnesses
+pub fn bench_sqr_rust(c: &mut Criterion) {
+    c.bench_function("8. Squaring in Rust X 1000 iterations", |b| {
+        b.iter(|| sqr_rust(&X, &EXP_SQR))
+    });
+}
+
+pub fn bench_sqr_asm(c: &mut Criterion) {
+    c.bench_function("9. Squaring in Rust with assembly X 1000 iterations", |b| {
+        b.iter(|| sqr_asm(&X, &EXP_SQR))
+    });
+}
+
+pub fn bench_mul_bn254(c: &mut Criterion) {
+    c.bench_function("10. Multiplication in BN254 Fq X 1000 iterations", |b| {
+        b.iter(|| mul_field(&BN254_FQ, &BN254_FQ_XY_EXP))
+    });
+    c.bench_function("11. Multiplication in BN254 Fr X 1000 iterations", |b| {
+        b.iter(|| mul_field(&BN254_FR, &BN254_FR_XY_EXP))
+    });
+}
+
+pub fn bench_mul_bls12_377(c: &mut Criterion) {
+    c.bench_function("12. Multiplication in BLS12-377 Fq X 1000 iterations", |b| {
+        b.iter(|| mul_field(&BLS12_377_FQ, &BLS12_377_FQ_XY_EXP))
+    });
+    c.bench_function("13. Multiplication in BLS12-377 Fr X 1000 iterations", |b| {
+        b.iter(|| mul_field(&BLS12_377_FR, &BLS12_377_FR_XY_EXP))
+    });
+}
+
+// The same BN254 Fq and BLS12-377 Fq work as above without the generated assembly, to compare
+pub fn bench_mul_portable(c: &mut Criterion) {
+    let bn254_fq = MontField { asm_mul: None, ..BN254_FQ };
+    c.bench_function("14. Multiplication in BN254 Fq without assembly X 1000 iterations", |b| {
+        b.iter(|| mul_field(&bn254_fq, &BN254_FQ_XY_EXP))
+    });
+    let bls12_377_fq = MontField { asm_mul: None, ..BLS12_377_FQ };
+    c.bench_function(
+        "15. Multiplication in BLS12-377 Fq without assembly X 1000 iterations",
+        |b| b.iter(|| mul_field(&bls12_377_fq, &BLS12_377_FQ_XY_EXP)),
+    );
+}
+
+// Harness for Miller loop line multiplication, dense against sparse, checking each result
+pub fn bench_mul_fp12_sparse(c: &mut Criterion) {
+    let f = fp12_from_u64(7);
+    let (b0, b1, b3, b4) = (fp2_from_u64(11), fp2_from_u64(13), fp2_from_u64(17), fp2_from_u64(19));
+    let line_014 = Fp12::new(Fp6::new(b0, b1, Fp2::ZERO), Fp6::new(Fp2::ZERO, b4, Fp2::ZERO));
+    let line_034 = Fp12::new(Fp6::new(b0, Fp2::ZERO, Fp2::ZERO), Fp6::new(b3, b4, Fp2::ZERO));
+    let (exp_014, exp_034) = (mul_fp12_dense(&f, &line_014), mul_fp12_dense(&f, &line_034));
+    c.bench_function("16. Multiplication in Fp12 by a dense line X 1000 iterations", |b| {
+        b.iter(|| assert_eq!(mul_fp12_dense(&f, &line_014), exp_014))
+    });
+    c.bench_function("17. Multiplication in Fp12 by a sparse 014 line X 1000 iterations", |b| {
+        b.iter(|| assert_eq!(mul_fp12_014(&f, &b0, &b1, &b4), exp_014))
+    });
+    c.bench_function("18. Multiplication in Fp12 by a sparse 034 line X 1000 iterations", |b| {
+        b.iter(|| assert_eq!(mul_fp12_034(&f, &b0, &b3, &b4), exp_034))
+    });
+}
+
+// Harness for squaring in the cyclotomic subgroup, plus decompressing 16 results singly or batched
+pub fn bench_sqr_cyclotomic(c: &mut Criterion) {
+    let a = fp12_from_u64(23);
+    let f = a.conjugate() * a.invert().0;
+    let f = f.frobenius_map(2) * f; // Now in the cyclotomic subgroup
+    let expected = sqr_fp12(&f);
+    c.bench_function("19. Squaring in Fp12 X 1000 iterations", |b| {
+        b.iter(|| assert_eq!(sqr_fp12(&f), expected))
+    });
+    c.bench_function("20. Cyclotomic squaring in Fp12 X 1000 iterations", |b| {
+        b.iter(|| assert_eq!(sqr_fp12_cyclotomic(&f), expected))
+    });
+    c.bench_function("21. Compressed squaring in Fp12 X 1000 iterations", |b| {
+        b.iter(|| assert_eq!(sqr_fp12_compressed(&f), expected))
+    });
+
+    let compressed: Vec<Fp12Compressed> =
+        (0..16).scan(f.compress(), |acc, _| Some(std::mem::replace(acc, acc.square()))).collect();
+    let expected: Vec<Fp12> = compressed.iter().map(Fp12Compressed::decompress).collect();
+    c.bench_function("22. Decompression in Fp12 X 16 single", |b| {
+        b.iter(|| {
+            assert_eq!(
+                compressed.iter().map(Fp12Compressed::decompress).collect::<Vec<_>>(),
+                expected
+            )
+        })
+    });
+    c.bench_function("23. Decompression in Fp12 X 16 batched", |b| {
+        b.iter(|| {
+            let mut result = [Fp12::ZERO; 16];
+            Fp12Compressed::batch_decompress(&compressed, &mut result);
+            assert_eq!(result.to_vec(), expected)
+        })
+    });
+}
+
+// Harness comparing the Fp2 -> Fp6 -> Fp12 tower with Fp2 -> Fp4 -> Fp12 (see 19 and 20 for the
+// standard tower's squarings); All results are mapped back and checked against the standard tower
+pub fn bench_fp12_towers(c: &mut Criterion) {
+    let (f, g) = (fp12_from_u64(29), fp12_from_u64(31));
+    let (f_alt, g_alt) = (Fp12Alt::from(f), Fp12Alt::from(g));
+    let exp_mul = mul_fp12(&f, &g);
+    c.bench_function("24. Multiplication in Fp12 X 1000 iterations", |b| {
+        b.iter(|| assert_eq!(mul_fp12(&f, &g), exp_mul))
+    });
+    c.bench_function("25. Multiplication in Fp12 over Fp4 X 1000 iterations", |b| {
+        b.iter(|| assert_eq!(Fp12::from(mul_fp12_alt(&f_alt, &g_alt)), exp_mul))
+    });
+
+    let cyclotomic = f.conjugate() * f.invert().0;
+    let cyclotomic = cyclotomic.frobenius_map(2) * cyclotomic;
+    let cyclotomic_alt = Fp12Alt::from(cyclotomic);
+    let exp_sqr = sqr_fp12(&cyclotomic);
+    c.bench_function("26. Squaring in Fp12 over Fp4 X 1000 iterations", |b| {
+        b.iter(|| assert_eq!(Fp12::from(sqr_fp12_alt(&cyclotomic_alt)), exp_sqr))
+    });
+    c.bench_function("27. Cyclotomic squaring in Fp12 over Fp4 X 1000 iterations", |b| {
+        b.iter(|| assert_eq!(Fp12::from(sqr_fp12_alt_cyclotomic(&cyclotomic_alt)), exp_sqr))
+    });
+}
+
+// Run all fifteen harnesses
 criterion_group! {
     name = benches;
     config = Criterion::default().measurement_time(Duration::new(60, 0));
     targets = bench_add, bench_sub, bench_mul_big, bench_mul_rust, bench_mul_rust_raw,
-    bench_mul_rust_intrinsics, bench_mul_asm
+    bench_mul_rust_intrinsics, bench_mul_asm, bench_sqr_rust, bench_sqr_asm, bench_mul_bn254,
+    bench_mul_bls12_377, bench_mul_portable, bench_mul_fp12_sparse,
+    bench_sqr_cyclotomic, bench_fp12_towers
 }
 criterion_main!(benches);
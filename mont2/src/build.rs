@@ -1,7 +1,149 @@
 // Copyright 2021 Eric Schorn; Licensed under the 3-Clause BSD License.
 
+#[cfg(feature = "asm")]
 extern crate cc;
 
+#[cfg(feature = "asm")]
+#[allow(dead_code)]
+#[path = "moduli.rs"]
+mod moduli;
+
+// The assembly (and with it the C toolchain requirement) is only needed by the `asm` feature, and
+// is x86-64 only; Build scripts run on the host, so the target is checked via cargo's environment
 fn main() {
-    cc::Build::new().file("src/mont_mul_asm.S").compile("mont_mul_asm");
+    #[cfg(feature = "asm")]
+    if std::env::var("CARGO_CFG_TARGET_ARCH").map_or(false, |arch| arch == "x86_64") {
+        use moduli::{BLS12_377_Q, BLS12_377_R, BLS12_381_R, BN254_Q, BN254_R, P448, P512};
+
+        println!("cargo:rerun-if-changed=src/build.rs");
+        println!("cargo:rerun-if-changed=src/moduli.rs");
+        println!("cargo:rerun-if-changed=src/mont_mul_asm.S");
+        println!("cargo:rerun-if-changed=src/mont_sqr_asm.S");
+
+        let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR").unwrap());
+        let mut build = cc::Build::new();
+        build.file("src/mont_mul_asm.S").file("src/mont_sqr_asm.S");
+        let generated: [(&str, &[u64]); 7] = [
+            ("bls12_381_r", &BLS12_381_R),
+            ("bn254_q", &BN254_Q),
+            ("bn254_r", &BN254_R),
+            ("bls12_377_q", &BLS12_377_Q),
+            ("bls12_377_r", &BLS12_377_R),
+            ("p448", &P448),
+            ("p512", &P512),
+        ];
+        for (name, n) in &generated {
+            let path = out_dir.join(format!("mont_mul_asm_{}.S", name));
+            std::fs::write(&path, gen_mont_mul(&format!("mont_mul_asm_{}", name), n)).unwrap();
+            build.file(path);
+        }
+        build.compile("mont_asm");
+    }
+}
+
+// Emit a mulx/adcx/adox Montgomery multiplication specialized to the odd modulus n, following
+// the same CIOS structure as mont_mul_asm.S but for any of 2 to 8 limbs; The working result sits
+// in registers with two extra words on top, so moduli using every bit of their limbs are fine
+#[cfg(feature = "asm")]
+#[allow(clippy::needless_range_loop)]
+fn gen_mont_mul(name: &str, n: &[u64]) -> String {
+    let limbs = n.len();
+    assert!((2..=8).contains(&limbs) && n[0] & 1 == 1);
+    let regs = ["%rdi", "%rbp", "%r8", "%r9", "%r10", "%r11", "%r12", "%r13", "%r14", "%r15"];
+    let (t, top, extra) = (&regs[0..limbs], regs[limbs], regs[limbs + 1]);
+    let above = |j: usize| if j + 1 < limbs { t[j + 1] } else { top };
+
+    // -N^{-1} mod 2^64 via Newton iteration, as field.rs does at compile time
+    let mut inv = 1_u64;
+    for _ in 0..6 {
+        inv = inv.wrapping_mul(2_u64.wrapping_sub(n[0].wrapping_mul(inv)));
+    }
+    let n_prime = inv.wrapping_neg();
+
+    let mut s = String::new();
+    let mut line = |text: String| {
+        s.push_str(&text);
+        s.push('\n');
+    };
+    line("# Generated by build.rs from moduli.rs; Do not edit".to_string());
+    line(".text".to_string());
+    line(format!(".global {}\n.global _{}", name, name));
+    line(".align 8".to_string());
+    line(format!("{}_N:                           # Field modulus, LSB first", name));
+    for limb in n {
+        line(format!(".quad   {:#018x}", limb));
+    }
+    line(String::new());
+    line("# Montgomery mult; assumes properly reduced input operands".to_string());
+    line(
+        "# %rdi holds address of result, %rsi and %rdx hold addresses of operands a & b"
+            .to_string(),
+    );
+    line(format!("_{}:\n{}:", name, name));
+    for reg in &["%rbx", "%rbp", "%r12", "%r13", "%r14", "%r15", "%rdi"] {
+        line(format!("    pushq   {}", reg));
+    }
+    line("    movq    %rdx, %rcx              # Hang on to address of b".to_string());
+    for reg in t.iter().chain([top].iter()) {
+        line(format!("    xorq    {}, {}", reg, reg));
+    }
+
+    for i in 0..limbs {
+        // Add a * b[i] into the working result, lo halves on the CF chain and hi halves on OF
+        line(format!("\n    # Partial product {}", i));
+        line(format!("    movq    {}(%rcx), %rdx", 8 * i));
+        line(format!("    xorq    {}, {}              # Clear flags", extra, extra));
+        for j in 0..limbs {
+            line(format!("    mulxq   {}(%rsi), %rax, %rbx", 8 * j));
+            line(format!("    adcxq   %rax, {}", t[j]));
+            line(format!("    adoxq   %rbx, {}", above(j)));
+        }
+        line("    movq    $0, %rax".to_string());
+        line(format!("    adcxq   %rax, {}", top));
+        line(format!("    adoxq   %rax, {}", extra));
+        line(format!("    adcxq   %rax, {}", extra));
+
+        // Add m * N, which clears the lowest word, while shifting everything down by one word
+        line(format!("\n    # Reduction {}", i));
+        line(format!("    movabsq ${:#018x}, %rdx", n_prime));
+        line(format!("    imulq   {}, %rdx", t[0]));
+        line("    xorq    %rax, %rax              # Clear flags".to_string());
+        line(format!("    mulxq   {}_N(%rip), %rax, %rbx", name));
+        line(format!("    adcxq   {}, %rax", t[0]));
+        line(format!("    adoxq   %rbx, {}", above(0)));
+        for j in 1..limbs {
+            line(format!("    mulxq   {}_N+{}(%rip), {}, %rbx", name, 8 * j, t[j - 1]));
+            line(format!("    adcxq   {}, {}", t[j], t[j - 1]));
+            line(format!("    adoxq   %rbx, {}", above(j)));
+        }
+        line(format!("    movq    {}, {}", top, t[limbs - 1]));
+        line("    movq    $0, %rax".to_string());
+        line(format!("    adcxq   %rax, {}", t[limbs - 1]));
+        line(format!("    movq    {}, {}", extra, top));
+        line(format!("    adcxq   %rax, {}", top));
+        line(format!("    adoxq   %rax, {}", top));
+    }
+
+    // The result is below 2N; Store it, subtract N and keep the stored copy if that borrowed
+    line("\n    # Final correction".to_string());
+    line("    movq    (%rsp), %rax            # Address of result".to_string());
+    for (j, reg) in t.iter().enumerate() {
+        line(format!("    movq    {}, {}(%rax)", reg, 8 * j));
+    }
+    line(format!("    subq    {}_N(%rip), {}", name, t[0]));
+    for (j, reg) in t.iter().enumerate().skip(1) {
+        line(format!("    sbbq    {}_N+{}(%rip), {}", name, 8 * j, reg));
+    }
+    line(format!("    sbbq    $0, {}", top));
+    for (j, reg) in t.iter().enumerate() {
+        line(format!("    cmovcq  {}(%rax), {}", 8 * j, reg));
+    }
+    for (j, reg) in t.iter().enumerate() {
+        line(format!("    movq    {}, {}(%rax)", reg, 8 * j));
+    }
+    for reg in &["%rdi", "%r15", "%r14", "%r13", "%r12", "%rbp", "%rbx"] {
+        line(format!("    popq    {}", reg));
+    }
+    line("    ret".to_string());
+    s
 }
@@ -0,0 +1,134 @@
+// Copyright 2021 Eric Schorn; Licensed under the 3-Clause BSD License.
+
+use crate::arith::{self, W6x64};
+#[cfg(all(feature = "asm", target_arch = "x86_64"))]
+use crate::{fe_mont_mul_asm, fe_mont_sqr_asm};
+use core::sync::atomic::{AtomicU8, Ordering};
+
+// The multiplication implementations; Intrinsics and Asm need both BMI2 and ADX at runtime, as
+// well as their cargo feature at build time
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    Portable,
+    Intrinsics,
+    Asm,
+}
+
+// Zero means not yet decided; Otherwise holds a Backend encoded by to_u8()
+static SELECTED: AtomicU8 = AtomicU8::new(0);
+
+impl Backend {
+    fn to_u8(self) -> u8 {
+        match self {
+            Backend::Portable => 1,
+            Backend::Intrinsics => 2,
+            Backend::Asm => 3,
+        }
+    }
+
+    fn from_u8(x: u8) -> Backend {
+        match x {
+            3 => Backend::Asm,
+            2 => Backend::Intrinsics,
+            _ => Backend::Portable,
+        }
+    }
+
+    // True when this backend was built in and the running CPU can execute it
+    #[must_use]
+    pub fn is_supported(self) -> bool {
+        let built = match self {
+            Backend::Portable => return true,
+            Backend::Intrinsics => cfg!(all(feature = "intrinsics", target_arch = "x86_64")),
+            Backend::Asm => cfg!(all(feature = "asm", target_arch = "x86_64")),
+        };
+        built && has_bmi2_adx()
+    }
+}
+
+// Reads CPUID directly rather than via is_x86_feature_detected! so it also works without std;
+// Leaf 7 reports BMI2 in EBX bit 8 and ADX in EBX bit 19
+#[cfg(target_arch = "x86_64")]
+#[allow(unused_unsafe)] // The CPUID intrinsics are unsafe on older toolchains only
+fn has_bmi2_adx() -> bool {
+    use core::arch::x86_64::{__cpuid, __cpuid_count};
+    let ebx = unsafe {
+        if __cpuid(0).eax < 7 {
+            return false;
+        }
+        __cpuid_count(7, 0).ebx
+    };
+    (ebx >> 8) & (ebx >> 19) & 1 == 1
+}
+
+// Elsewhere only the portable backend exists
+#[cfg(not(target_arch = "x86_64"))]
+fn has_bmi2_adx() -> bool {
+    false
+}
+
+// The fastest backend the running CPU supports
+#[must_use]
+pub fn detect_backend() -> Backend {
+    if Backend::Asm.is_supported() {
+        Backend::Asm
+    } else if Backend::Intrinsics.is_supported() {
+        Backend::Intrinsics
+    } else {
+        Backend::Portable
+    }
+}
+
+// The backend currently used by fe_mont_mul and fe_mont_sqr, detecting it on first use; Every
+// call costs a relaxed atomic load, and the callers then branch on the result
+#[must_use]
+pub fn backend() -> Backend {
+    match SELECTED.load(Ordering::Relaxed) {
+        0 => {
+            let detected = detect_backend();
+            SELECTED.store(detected.to_u8(), Ordering::Relaxed);
+            detected
+        }
+        x => Backend::from_u8(x),
+    }
+}
+
+// Pin every subsequent call to one backend (mainly for testing), or None to go back to detection;
+// Returns false and changes nothing when the running CPU does not support the requested backend
+#[must_use]
+pub fn force_backend(backend: Option<Backend>) -> bool {
+    match backend {
+        Some(forced) if !forced.is_supported() => false,
+        Some(forced) => {
+            SELECTED.store(forced.to_u8(), Ordering::Relaxed);
+            true
+        }
+        None => {
+            SELECTED.store(0, Ordering::Relaxed);
+            true
+        }
+    }
+}
+
+// Effectively result_mont = (a_mont * b_mont * R^{-1}) mod N on the selected backend
+pub fn fe_mont_mul(result: &mut W6x64, a: &W6x64, b: &W6x64) {
+    match backend() {
+        #[cfg(all(feature = "asm", target_arch = "x86_64"))]
+        Backend::Asm => unsafe { fe_mont_mul_asm(&mut result.v[0], &a.v[0], &b.v[0]) },
+        #[cfg(all(feature = "intrinsics", target_arch = "x86_64"))]
+        Backend::Intrinsics => arith::fe_mont_mul_intrinsics(result, a, b),
+        _ => arith::fe_mont_mul_portable(result, a, b),
+    }
+}
+
+// Effectively result_mont = (a_mont * a_mont * R^{-1}) mod N on the selected backend; There is
+// no dedicated intrinsics squaring, so that backend multiplies a by itself
+pub fn fe_mont_sqr(result: &mut W6x64, a: &W6x64) {
+    match backend() {
+        #[cfg(all(feature = "asm", target_arch = "x86_64"))]
+        Backend::Asm => unsafe { fe_mont_sqr_asm(&mut result.v[0], &a.v[0]) },
+        #[cfg(all(feature = "intrinsics", target_arch = "x86_64"))]
+        Backend::Intrinsics => arith::fe_mont_mul_intrinsics(result, a, a),
+        _ => arith::fe_mont_sqr_portable(result, a),
+    }
+}
@@ -0,0 +1,400 @@
+// Copyright 2021 Eric Schorn; Licensed under the 3-Clause BSD License.
+
+use crate::arith::{Choice, N, R_MOD_N, R_SQUARED};
+#[cfg(all(feature = "asm", target_arch = "x86_64"))]
+use crate::dispatch::{self, Backend};
+use crate::moduli::{BLS12_377_Q, BLS12_377_R, BLS12_381_R, BN254_Q, BN254_R, P448, P512};
+
+// The assembly Montgomery multiplications available: the hand-written one for BLS12-381 and those
+// build.rs generates from moduli.rs; Each only applies to its own modulus
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AsmMul {
+    Bls12_381Fp,
+    Bls12_381Fr,
+    Bn254Fq,
+    Bn254Fr,
+    Bls12_377Fq,
+    Bls12_377Fr,
+    P448,
+    P512,
+}
+
+#[cfg(all(feature = "asm", target_arch = "x86_64"))]
+impl AsmMul {
+    // All of these share the calling convention of fe_mont_mul_asm
+    fn routine(self) -> unsafe extern "C" fn(&mut u64, &u64, &u64) {
+        match self {
+            AsmMul::Bls12_381Fp => crate::fe_mont_mul_asm,
+            AsmMul::Bls12_381Fr => crate::mont_mul_asm_bls12_381_r,
+            AsmMul::Bn254Fq => crate::mont_mul_asm_bn254_q,
+            AsmMul::Bn254Fr => crate::mont_mul_asm_bn254_r,
+            AsmMul::Bls12_377Fq => crate::mont_mul_asm_bls12_377_q,
+            AsmMul::Bls12_377Fr => crate::mont_mul_asm_bls12_377_r,
+            AsmMul::P448 => crate::mont_mul_asm_p448,
+            AsmMul::P512 => crate::mont_mul_asm_p512,
+        }
+    }
+}
+
+// Montgomery arithmetic for any odd prime modulus N < R = 2^(64*LIMBS); Elements are plain limb
+// arrays, least significant limb first, exactly as the fe_* functions use them for BLS12-381
+#[derive(Clone, Copy, Debug)]
+pub struct MontField<const LIMBS: usize> {
+    pub n: [u64; LIMBS],          // The modulus N
+    pub n_prime: u64,             // -N^{-1} mod 2^64
+    pub r_mod_n: [u64; LIMBS],    // R mod N, i.e. one in Montgomery form
+    pub r_squared: [u64; LIMBS],  // R^2 mod N, for conversion into Montgomery form
+    pub correction: [u64; LIMBS], // R - N, which sub subtracts to add N back after a wrap
+    // Assembly for exactly this modulus, used by mont_mul when built and the Asm backend is selected
+    pub asm_mul: Option<AsmMul>,
+}
+
+// Fields are equal when their constants are; Which code multiplies does not matter
+impl<const LIMBS: usize> PartialEq for MontField<LIMBS> {
+    fn eq(&self, other: &Self) -> bool {
+        self.n == other.n
+            && self.n_prime == other.n_prime
+            && self.r_mod_n == other.r_mod_n
+            && self.r_squared == other.r_squared
+            && self.correction == other.correction
+    }
+}
+
+// The BLS12-381 base field; Its add, sub and mont_mul_portable are what fe_add, fe_sub and
+// fe_mont_mul_portable run
+pub const BLS12_381_FP: MontField<6> =
+    MontField { asm_mul: Some(AsmMul::Bls12_381Fp), ..MontField::new(N) };
+
+// The BLS12-381 scalar field behind Fr
+pub const BLS12_381_FR: MontField<4> =
+    MontField { asm_mul: Some(AsmMul::Bls12_381Fr), ..MontField::new(BLS12_381_R) };
+
+// BN254 (alt_bn128) base and scalar fields
+pub const BN254_FQ: MontField<4> =
+    MontField { asm_mul: Some(AsmMul::Bn254Fq), ..MontField::new(BN254_Q) };
+pub const BN254_FR: MontField<4> =
+    MontField { asm_mul: Some(AsmMul::Bn254Fr), ..MontField::new(BN254_R) };
+
+// BLS12-377 base and scalar fields
+pub const BLS12_377_FQ: MontField<6> =
+    MontField { asm_mul: Some(AsmMul::Bls12_377Fq), ..MontField::new(BLS12_377_Q) };
+pub const BLS12_377_FR: MontField<4> =
+    MontField { asm_mul: Some(AsmMul::Bls12_377Fr), ..MontField::new(BLS12_377_R) };
+
+// Seven and eight limb fields, whose moduli use every available bit
+pub const P448_FP: MontField<7> = MontField { asm_mul: Some(AsmMul::P448), ..MontField::new(P448) };
+pub const P512_FP: MontField<8> = MontField { asm_mul: Some(AsmMul::P512), ..MontField::new(P512) };
+
+// Fails to compile (on a mismatched array length) unless the condition holds; Panicking in a
+// constant would be clearer but needs Rust 1.57
+macro_rules! const_assert {
+    ($cond:expr) => {
+        const _: [(); 0] = [(); !$cond as usize];
+    };
+}
+
+// The constants derived at compile time must match the literals the fe_* functions rely upon
+const_assert!(limbs_eq(BLS12_381_FP.r_mod_n, R_MOD_N.v));
+const_assert!(limbs_eq(BLS12_381_FP.r_squared, R_SQUARED.v));
+
+// Effectively -N^{-1} mod 2^64 for the odd lowest limb n0 via Newton iteration; Each step doubles
+// the count of correct low bits, starting from the single bit that 1 already gets right
+#[must_use]
+pub const fn neg_inv_mod_2_64(n0: u64) -> u64 {
+    let mut inv = 1_u64;
+    let mut i = 0;
+    while i < 6 {
+        inv = inv.wrapping_mul(2_u64.wrapping_sub(n0.wrapping_mul(inv)));
+        i += 1;
+    }
+    inv.wrapping_neg()
+}
+
+// Effectively 2^(64*LIMBS*power) mod N, by doubling one modulo N that many times; Intended for
+// compile time, where the branching and the bit-at-a-time pace are of no concern
+#[must_use]
+pub const fn pow2_mod<const LIMBS: usize>(n: [u64; LIMBS], power: usize) -> [u64; LIMBS] {
+    let mut x = [0_u64; LIMBS];
+    x[0] = 1;
+    let mut i = 0;
+    while i < 64 * LIMBS * power {
+        x = double_mod(x, n);
+        i += 1;
+    }
+    x
+}
+
+// Effectively 2^(64*LIMBS) - N, i.e. the two's complement negation of N
+#[must_use]
+pub const fn correction<const LIMBS: usize>(n: [u64; LIMBS]) -> [u64; LIMBS] {
+    let mut result = [0_u64; LIMBS];
+    let mut carry = 1_u64;
+    let mut i = 0;
+    while i < LIMBS {
+        let (sum, car) = (!n[i]).overflowing_add(carry);
+        result[i] = sum;
+        carry = car as u64;
+        i += 1;
+    }
+    result
+}
+
+// Effectively (2 * x) mod N for x < N, where 2x may carry out of the top limb
+const fn double_mod<const LIMBS: usize>(x: [u64; LIMBS], n: [u64; LIMBS]) -> [u64; LIMBS] {
+    let mut doubled = [0_u64; LIMBS];
+    let mut trial = [0_u64; LIMBS];
+    let mut shifted_in = 0_u64;
+    let mut borrow = 0_u64;
+    let mut i = 0;
+    while i < LIMBS {
+        doubled[i] = (x[i] << 1) | shifted_in;
+        shifted_in = x[i] >> 63;
+        let (dif_a, bor_a) = doubled[i].overflowing_sub(n[i]);
+        let (dif_b, bor_b) = dif_a.overflowing_sub(borrow);
+        trial[i] = dif_b;
+        borrow = (bor_a | bor_b) as u64;
+        i += 1;
+    }
+
+    // Keep the doubled value only when the subtraction borrowed beyond the carry out
+    if borrow > shifted_in {
+        doubled
+    } else {
+        trial
+    }
+}
+
+// Constant-time equality across all limbs
+#[must_use]
+pub fn ct_eq<const LIMBS: usize>(a: &[u64; LIMBS], b: &[u64; LIMBS]) -> Choice {
+    let diff = a.iter().zip(b.iter()).fold(0, |acc, (x, y)| acc | (x ^ y));
+    Choice::from_bit(((diff | diff.wrapping_neg()) >> 63) ^ 1)
+}
+
+// Returns b when choice is true, otherwise a
+#[must_use]
+pub fn conditional_select<const LIMBS: usize>(
+    a: &[u64; LIMBS],
+    b: &[u64; LIMBS],
+    choice: Choice,
+) -> [u64; LIMBS] {
+    let mut result = [0_u64; LIMBS];
+    for (r, (x, y)) in result.iter_mut().zip(a.iter().zip(b.iter())) {
+        *r = x ^ (choice.mask() & (x ^ y));
+    }
+    result
+}
+
+#[allow(dead_code)] // Only used within const_assert!, which Rust 1.54 overlooks
+const fn limbs_eq<const LIMBS: usize>(a: [u64; LIMBS], b: [u64; LIMBS]) -> bool {
+    let mut i = 0;
+    while i < LIMBS {
+        if a[i] != b[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+#[allow(clippy::needless_range_loop)]
+impl<const LIMBS: usize> MontField<LIMBS> {
+    // Derive every constant from the odd prime modulus N alone, at compile time when used in a const;
+    // Any generated assembly has to be attached separately
+    #[must_use]
+    pub const fn new(n: [u64; LIMBS]) -> Self {
+        MontField {
+            n,
+            n_prime: neg_inv_mod_2_64(n[0]),
+            r_mod_n: pow2_mod(n, 1),
+            r_squared: pow2_mod(n, 2),
+            correction: correction(n),
+            asm_mul: None,
+        }
+    }
+
+    // Effectively result = (a + b) mod N; Assume properly reduced inputs and outputs
+    pub fn add(&self, result: &mut [u64; LIMBS], a: &[u64; LIMBS], b: &[u64; LIMBS]) {
+        let mut sum = [0_u64; LIMBS];
+        let mut carry = false;
+        for i in 0..LIMBS {
+            let sum_car_a = a[i].overflowing_add(b[i]);
+            let sum_car_b = sum_car_a.0.overflowing_add(u64::from(carry));
+            sum[i] = sum_car_b.0;
+            carry = sum_car_a.1 | sum_car_b.1;
+        }
+        self.sub_n_if_above(result, &sum, carry);
+    }
+
+    // Effectively result = (a - b) mod N; Assume properly reduced inputs and outputs
+    pub fn sub(&self, result: &mut [u64; LIMBS], a: &[u64; LIMBS], b: &[u64; LIMBS]) {
+        let mut diff = [0_u64; LIMBS];
+        let mut borrow = false;
+        for i in 0..LIMBS {
+            let dif_bor_a = a[i].overflowing_sub(b[i]);
+            let dif_bor_b = dif_bor_a.0.overflowing_sub(u64::from(borrow));
+            diff[i] = dif_bor_b.0;
+            borrow = dif_bor_a.1 | dif_bor_b.1;
+        }
+
+        // Add N back when the subtraction wrapped, by subtracting R - N; The final borrow out simply
+        // cancels the wrap
+        let mask = Choice::from_bit(u64::from(borrow)).mask();
+        borrow = false;
+        for i in 0..LIMBS {
+            let dif_bor_a = diff[i].overflowing_sub(mask & self.correction[i]);
+            let dif_bor_b = dif_bor_a.0.overflowing_sub(u64::from(borrow));
+            result[i] = dif_bor_b.0;
+            borrow = dif_bor_a.1 | dif_bor_b.1;
+        }
+    }
+
+    // Effectively result_mont = (a_mont * b_mont * R^{-1}) mod N on the selected backend; Assume
+    // properly reduced input/output
+    pub fn mont_mul(&self, result: &mut [u64; LIMBS], a: &[u64; LIMBS], b: &[u64; LIMBS]) {
+        #[cfg(all(feature = "asm", target_arch = "x86_64"))]
+        if let Some(asm_mul) = self.asm_mul {
+            if dispatch::backend() == Backend::Asm {
+                unsafe { asm_mul.routine()(&mut result[0], &a[0], &b[0]) };
+                return;
+            }
+        }
+        self.mont_mul_portable(result, a, b);
+    }
+
+    // As above, but always via the attached assembly; Intended for testing and benchmarking, so
+    // panics when there is none or the running CPU lacks BMI2/ADX
+    #[cfg(all(feature = "asm", target_arch = "x86_64"))]
+    #[allow(clippy::missing_panics_doc)]
+    pub fn mont_mul_asm(&self, result: &mut [u64; LIMBS], a: &[u64; LIMBS], b: &[u64; LIMBS]) {
+        let asm_mul = self.asm_mul.expect("no assembly for this modulus");
+        assert!(Backend::Asm.is_supported());
+        unsafe { asm_mul.routine()(&mut result[0], &a[0], &b[0]) };
+    }
+
+    // As above, but always via the coarsely integrated operand scanning method in plain Rust
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn mont_mul_portable(&self, result: &mut [u64; LIMBS], a: &[u64; LIMBS], b: &[u64; LIMBS]) {
+        // The running value is temp plus two extra words above it, since N may use every bit
+        let mut temp = [0_u64; LIMBS];
+        let mut top = 0_u64;
+        for i in 0..LIMBS {
+            let mut carry = 0_u64;
+            for j in 0..LIMBS {
+                // Note (2^64-1)*(2^64-1)+2*(2^64-1) = 2^128-1
+                let hilo =
+                    u128::from(a[j]) * u128::from(b[i]) + u128::from(temp[j]) + u128::from(carry);
+                temp[j] = hilo as u64;
+                carry = (hilo >> 64) as u64;
+            }
+            let (top_lo, top_hi) = top.overflowing_add(carry);
+
+            // Adding m * N clears the lowest word, which is then shifted out
+            let m: u64 = temp[0].wrapping_mul(self.n_prime);
+            let hilo = u128::from(m) * u128::from(self.n[0]) + u128::from(temp[0]);
+            let mut carry = (hilo >> 64) as u64;
+            for j in 1..LIMBS {
+                let hilo =
+                    u128::from(m) * u128::from(self.n[j]) + u128::from(temp[j]) + u128::from(carry);
+                temp[j - 1] = hilo as u64;
+                carry = (hilo >> 64) as u64;
+            }
+            let (sum, sum_car) = top_lo.overflowing_add(carry);
+            temp[LIMBS - 1] = sum;
+            top = u64::from(top_hi) + u64::from(sum_car);
+        }
+
+        // The result is below 2N, so at most one subtraction is needed
+        self.sub_n_if_above(result, &temp, top != 0);
+    }
+
+    // Effectively a_mont = (a_norm * R) mod N
+    pub fn to_mont(&self, result: &mut [u64; LIMBS], a: &[u64; LIMBS]) {
+        self.mont_mul(result, a, &self.r_squared);
+    }
+
+    // Effectively a_norm = (a_mont * R^{-1}) mod N
+    pub fn to_norm(&self, result: &mut [u64; LIMBS], a: &[u64; LIMBS]) {
+        let mut one = [0_u64; LIMBS];
+        one[0] = 1;
+        self.mont_mul(result, a, &one);
+    }
+
+    // True when x < N, i.e. x is a canonical (normal form) value; Only the answer leaks
+    #[must_use]
+    pub fn is_canonical(&self, x: &[u64; LIMBS]) -> bool {
+        // A final borrow from x - N is what marks x < N
+        let mut borrow = false;
+        for i in 0..LIMBS {
+            let (diff0, borrow0) = x[i].overflowing_sub(self.n[i]);
+            let (_, borrow1) = diff0.overflowing_sub(u64::from(borrow));
+            borrow = borrow0 | borrow1;
+        }
+        borrow
+    }
+
+    // Effectively result_mont = ((hi * 2^(64*LIMBS) + lo) * R) mod N for any lo and hi, e.g. to
+    // reduce a uniformly random double-width value with negligible bias
+    pub fn reduce_wide(&self, result: &mut [u64; LIMBS], lo: &[u64; LIMBS], hi: &[u64; LIMBS]) {
+        // Any a < R times b < N is below N * R, so the portable multiplication accepts unreduced
+        // halves; The hand-written assembly does not, so it is bypassed for these two
+        let (mut lo_mont, mut hi_mont, mut hi_mont_r) = ([0; LIMBS], [0; LIMBS], [0; LIMBS]);
+        self.mont_mul_portable(&mut lo_mont, lo, &self.r_squared);
+        self.mont_mul_portable(&mut hi_mont, hi, &self.r_squared);
+        self.mont_mul(&mut hi_mont_r, &hi_mont, &self.r_squared);
+        self.add(result, &lo_mont, &hi_mont_r);
+    }
+
+    // Effectively result_mont = a_mont^exponent mod N by square-and-multiply; Not constant-time!!
+    // Intended for public exponents only; Exponent limbs are least significant first
+    pub fn pow_vartime(&self, result: &mut [u64; LIMBS], a: &[u64; LIMBS], exponent: &[u64]) {
+        let mut acc = self.r_mod_n;
+        let mut temp = [0_u64; LIMBS];
+        let mut started = false;
+        for limb in exponent.iter().rev() {
+            for bit in (0..64).rev() {
+                if started {
+                    self.mont_mul(&mut temp, &acc, &acc);
+                    acc = temp;
+                }
+                if (limb >> bit) & 1 == 1 {
+                    self.mont_mul(&mut temp, &acc, a);
+                    acc = temp;
+                    started = true;
+                }
+            }
+        }
+        *result = acc;
+    }
+
+    // Effectively result_mont = (a_mont^{-1}) mod N as a_mont^(N-2) by Fermat's little theorem;
+    // The exponent is public, so the timing is independent of a; Returns true (with a zero result)
+    // when a is zero
+    pub fn inv(&self, result: &mut [u64; LIMBS], a: &[u64; LIMBS]) -> Choice {
+        let mut exponent = self.n;
+        let mut borrow = 2_u64;
+        for limb in &mut exponent {
+            let (diff, bor) = limb.overflowing_sub(borrow);
+            *limb = diff;
+            borrow = u64::from(bor);
+        }
+        self.pow_vartime(result, a, &exponent);
+        ct_eq(a, &[0; LIMBS])
+    }
+
+    // Effectively result = x mod N for the LIMBS+1 word value (carry, x) below 2N, in constant time
+    fn sub_n_if_above(&self, result: &mut [u64; LIMBS], x: &[u64; LIMBS], carry: bool) {
+        let mut trial = [0_u64; LIMBS];
+        let mut borrow = false;
+        for i in 0..LIMBS {
+            let dif_bor_a = x[i].overflowing_sub(self.n[i]);
+            let dif_bor_b = dif_bor_a.0.overflowing_sub(u64::from(borrow));
+            trial[i] = dif_bor_b.0;
+            borrow = dif_bor_a.1 | dif_bor_b.1;
+        }
+
+        // Keep x only when the subtraction borrowed beyond the carry word
+        let select_x = Choice::from_bit(u64::from(borrow) & !u64::from(carry));
+        *result = conditional_select(&trial, x, select_x);
+    }
+}
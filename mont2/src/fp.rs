@@ -0,0 +1,137 @@
+// Copyright 2021 Eric Schorn; Licensed under the 3-Clause BSD License.
+
+use crate::arith::{
+    fe_add, fe_inv, fe_sub, fe_to_mont, fe_to_norm, Choice, FieldError, W6x64, FE_BYTES, R_MOD_N,
+};
+use crate::dispatch::{fe_mont_mul, fe_mont_sqr};
+use core::ops::{Add, Mul, Neg, Sub};
+
+// BLS12-381 base field element, always held fully reduced in Montgomery form
+#[derive(Default, Clone, Copy, Debug, PartialEq)] // PartialEq is not constant time; see ct_eq
+pub struct Fp(W6x64);
+
+impl Fp {
+    pub const ZERO: Fp = Fp(W6x64 { v: [0; 6] });
+    pub const ONE: Fp = Fp(R_MOD_N);
+
+    // Montgomery form limbs taken as they are, for constants; They must already be reduced
+    #[must_use]
+    pub(crate) const fn from_mont_unchecked(v: [u64; 6]) -> Fp {
+        Fp(W6x64 { v })
+    }
+
+    // Any u64 is already canonical
+    #[must_use]
+    pub fn from_u64(x: u64) -> Fp {
+        let mut result = W6x64::default();
+        fe_to_mont(&mut result, &[x, 0, 0, 0, 0, 0]);
+        Fp(result)
+    }
+
+    // Canonical value, least significant limb first; Refuses anything >= N
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_norm(norm: &[u64; 6]) -> Result<Fp, FieldError> {
+        W6x64::from_norm(norm).map(Fp)
+    }
+
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_bytes_be(bytes: &[u8; FE_BYTES]) -> Result<Fp, FieldError> {
+        W6x64::from_bytes_be(bytes).map(Fp)
+    }
+
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_bytes_le(bytes: &[u8; FE_BYTES]) -> Result<Fp, FieldError> {
+        W6x64::from_bytes_le(bytes).map(Fp)
+    }
+
+    // Canonical value, least significant limb first
+    #[must_use]
+    pub fn to_norm(&self) -> [u64; 6] {
+        let mut norm = [0_u64; 6];
+        fe_to_norm(&mut norm, &self.0);
+        norm
+    }
+
+    #[must_use]
+    pub fn to_bytes_be(&self) -> [u8; FE_BYTES] {
+        self.0.to_bytes_be()
+    }
+
+    #[must_use]
+    pub fn to_bytes_le(&self) -> [u8; FE_BYTES] {
+        self.0.to_bytes_le()
+    }
+
+    // The underlying Montgomery form limbs, for use with the fe_* functions
+    #[must_use]
+    pub fn as_mont(&self) -> &W6x64 {
+        &self.0
+    }
+
+    #[must_use]
+    pub fn square(&self) -> Fp {
+        let mut result = W6x64::default();
+        fe_mont_sqr(&mut result, &self.0);
+        Fp(result)
+    }
+
+    // Returns the inverse along with a Choice that is true when self is zero (and so is the result)
+    #[must_use]
+    pub fn invert(&self) -> (Fp, Choice) {
+        let mut result = W6x64::default();
+        let is_zero = fe_inv(&mut result, &self.0);
+        (Fp(result), is_zero)
+    }
+
+    #[must_use]
+    pub fn ct_eq(&self, other: &Fp) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+
+    #[must_use]
+    pub fn ct_is_zero(&self) -> Choice {
+        self.0.ct_is_zero()
+    }
+
+    // Returns b when choice is true, otherwise a
+    #[must_use]
+    pub fn conditional_select(a: &Fp, b: &Fp, choice: Choice) -> Fp {
+        Fp(W6x64::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl Add for Fp {
+    type Output = Fp;
+    fn add(self, rhs: Fp) -> Fp {
+        let mut result = W6x64::default();
+        fe_add(&mut result, &self.0, &rhs.0);
+        Fp(result)
+    }
+}
+
+impl Sub for Fp {
+    type Output = Fp;
+    fn sub(self, rhs: Fp) -> Fp {
+        let mut result = W6x64::default();
+        fe_sub(&mut result, &self.0, &rhs.0);
+        Fp(result)
+    }
+}
+
+impl Mul for Fp {
+    type Output = Fp;
+    fn mul(self, rhs: Fp) -> Fp {
+        let mut result = W6x64::default();
+        fe_mont_mul(&mut result, &self.0, &rhs.0);
+        Fp(result)
+    }
+}
+
+impl Neg for Fp {
+    type Output = Fp;
+    fn neg(self) -> Fp {
+        Fp::ZERO - self
+    }
+}
+
+impl_field_ops!(Fp);
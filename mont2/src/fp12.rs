@@ -0,0 +1,314 @@
+// Copyright 2021 Eric Schorn; Licensed under the 3-Clause BSD License.
+
+use crate::arith::Choice;
+use crate::fp::Fp;
+use crate::fp2::Fp2;
+use crate::fp6::{Fp6, FROB_B, FROB_E};
+use core::ops::{Add, Mul, Neg, Sub};
+
+// BLS12-381 element c0 + c1*w of Fp12 = Fp6[w]/(w^2 - v), the target of the pairing
+#[derive(Default, Clone, Copy, Debug, PartialEq)] // PartialEq is not constant time; see ct_eq
+pub struct Fp12 {
+    pub c0: Fp6,
+    pub c1: Fp6,
+}
+
+// (1 + u)^((N^k - 1)/6) for k = 0..5, since w^(N^k) = w * v^((N^k - 1)/2) is this multiple of w;
+// In Montgomery form, and checked against BigUint exponentiation in the tests
+#[rustfmt::skip]
+pub(crate) const FROBENIUS_COEFF_C1: [Fp2; 6] = [
+    Fp2 { c0: Fp::ONE, c1: Fp::ZERO },
+    Fp2 {
+        c0: Fp::from_mont_unchecked([
+            0x0708_9552_b319_d465, 0xc669_5f92_b50a_8313, 0x97e8_3ccc_d117_228f,
+            0xa35b_aeca_b2dc_29ee, 0x1ce3_93ea_5daa_ce4d, 0x08f2_220f_b0fb_66eb,
+        ]),
+        c1: Fp::from_mont_unchecked([
+            0xb2f6_6aad_4ce5_d646, 0x5842_a06b_fc49_7cec, 0xcf48_95d4_2599_d394,
+            0xc11b_9cba_40a8_e8d0, 0x2e38_13cb_e5a0_de89, 0x110e_efda_8884_7faf,
+        ]),
+    },
+    Fp2 { c0: FROB_E, c1: Fp::ZERO },
+    Fp2 {
+        c0: Fp::from_mont_unchecked([
+            0x3e2f_585d_a55c_9ad1, 0x4294_213d_86c1_8183, 0x3828_44c8_8b62_3732,
+            0x92ad_2afd_1910_3e18, 0x1d79_4e4f_ac7c_f0b9, 0x0bd5_92fc_7d82_5ec8,
+        ]),
+        c1: Fp::from_mont_unchecked([
+            0x7bcf_a7a2_5aa3_0fda, 0xdc17_dec1_2a92_7e7c, 0x2f08_8dd8_6b4e_bef1,
+            0xd1ca_2087_da74_d4a7, 0x2da2_5966_96ce_bc1d, 0x0e2b_7eed_bbfd_87d2,
+        ]),
+    },
+    Fp2 { c0: FROB_B, c1: Fp::ZERO },
+    Fp2 {
+        c0: Fp::from_mont_unchecked([
+            0x3726_c30a_f242_c66c, 0x7c2a_c1aa_d1b6_fe70, 0xa040_07fb_ba4b_14a2,
+            0xef51_7c32_6634_1429, 0x0095_ba65_4ed2_226b, 0x02e3_70ec_cc86_f7dd,
+        ]),
+        c1: Fp::from_mont_unchecked([
+            0x82d8_3cf5_0dbc_e43f, 0xa281_3e53_df9d_018f, 0xc6f0_caa5_3c65_e181,
+            0x7525_cf52_8d50_fe95, 0x4a85_ed50_f479_8a6b, 0x171d_a0fd_6cf8_eebd,
+        ]),
+    },
+];
+
+impl Fp12 {
+    pub const ZERO: Fp12 = Fp12 { c0: Fp6::ZERO, c1: Fp6::ZERO };
+    pub const ONE: Fp12 = Fp12 { c0: Fp6::ONE, c1: Fp6::ZERO };
+
+    #[must_use]
+    pub fn new(c0: Fp6, c1: Fp6) -> Fp12 {
+        Fp12 { c0, c1 }
+    }
+
+    // Complex squaring over Fp6, for two multiplications: with t = c0*c1, the result is
+    // (c0 + c1)(c0 + v*c1) - t - v*t + 2*t*w
+    #[must_use]
+    pub fn square(&self) -> Fp12 {
+        let t = self.c0 * self.c1;
+        let c0 = (self.c0 + self.c1) * (self.c0 + self.c1.mul_by_nonresidue())
+            - t
+            - t.mul_by_nonresidue();
+        Fp12 { c0, c1: t + t }
+    }
+
+    // Multiplication by a Miller loop line b0 + b1*v + b4*v*w, i.e. with only the Fp2 coefficients
+    // at positions 0, 1 and 4 (of c0.c0, c0.c1, c0.c2, c1.c0, c1.c1, c1.c2) non-zero; Karatsuba with
+    // sparse Fp6 products costs 13 multiplications in Fp2 rather than 18
+    #[must_use]
+    pub fn mul_by_014(&self, b0: &Fp2, b1: &Fp2, b4: &Fp2) -> Fp12 {
+        let v0 = self.c0.mul_by_01(b0, b1);
+        let v1 = self.c1.mul_by_1(b4);
+        Fp12 {
+            c0: v0 + v1.mul_by_nonresidue(),
+            c1: (self.c0 + self.c1).mul_by_01(b0, &(b1 + b4)) - v0 - v1,
+        }
+    }
+
+    // Multiplication by a Miller loop line b0 + (b3 + b4*v)*w, i.e. with only the Fp2 coefficients
+    // at positions 0, 3 and 4 non-zero; As above, for 13 multiplications in Fp2 rather than 18
+    #[must_use]
+    pub fn mul_by_034(&self, b0: &Fp2, b3: &Fp2, b4: &Fp2) -> Fp12 {
+        let v0 = self.c0.mul_by_0(b0);
+        let v1 = self.c1.mul_by_01(b3, b4);
+        Fp12 {
+            c0: v0 + v1.mul_by_nonresidue(),
+            c1: (self.c0 + self.c1).mul_by_01(&(b0 + b3), b4) - v0 - v1,
+        }
+    }
+
+    // Granger-Scott squaring for elements of the cyclotomic subgroup (those with
+    // self^(N^4 - N^2 + 1) = 1, as after the easy part of the final exponentiation); Viewing Fp12 as
+    // a cubic extension of Fp4 = Fp2[v*w], it takes three Fp4 squarings, i.e. nine Fp2 squarings
+    #[must_use]
+    pub fn cyclotomic_square(&self) -> Fp12 {
+        // (a + b*s)^2 in Fp4 = Fp2[s]/(s^2 - (1 + u)), via two squarings in Fp2 and one more
+        fn fp4_square(a: &Fp2, b: &Fp2) -> (Fp2, Fp2) {
+            let (a2, b2) = (a.square(), b.square());
+            (a2 + b2.mul_by_nonresidue(), (a + b).square() - a2 - b2)
+        }
+        // Given t from the Fp4 squaring, 3*t - 2*z and 3*t + 2*z respectively
+        let minus = |t: Fp2, z: Fp2| (t - z) + (t - z) + t;
+        let plus = |t: Fp2, z: Fp2| (t + z) + (t + z) + t;
+
+        let (t0, t1) = fp4_square(&self.c0.c0, &self.c1.c1);
+        let (t2, t3) = fp4_square(&self.c1.c0, &self.c0.c2);
+        let (t4, t5) = fp4_square(&self.c0.c1, &self.c1.c2);
+        Fp12 {
+            c0: Fp6::new(minus(t0, self.c0.c0), minus(t2, self.c0.c1), minus(t4, self.c0.c2)),
+            c1: Fp6::new(
+                plus(t5.mul_by_nonresidue(), self.c1.c0),
+                plus(t1, self.c1.c1),
+                plus(t3, self.c1.c2),
+            ),
+        }
+    }
+
+    // Karabina's compressed form of an element of the cyclotomic subgroup, which drops c0.c0 and
+    // c1.c1; Only meaningful for such elements
+    #[must_use]
+    pub fn compress(&self) -> Fp12Compressed {
+        Fp12Compressed { g1: self.c0.c1, g2: self.c0.c2, g3: self.c1.c0, g5: self.c1.c2 }
+    }
+
+    // Effectively c0 - c1*w = self^(N^6); For elements of the cyclotomic subgroup, such as pairing
+    // outputs after the final exponentiation, this is the inverse at the cost of a negation
+    #[must_use]
+    pub fn conjugate(&self) -> Fp12 {
+        Fp12 { c0: self.c0, c1: -self.c1 }
+    }
+
+    // Returns the inverse along with a Choice that is true when self is zero (and so is the
+    // result); self * conjugate = c0^2 - v*c1^2 lies in Fp6, leaving one Fp6 inversion
+    #[must_use]
+    pub fn invert(&self) -> (Fp12, Choice) {
+        let t = self.c0.square() - self.c1.square().mul_by_nonresidue();
+        let (t_inv, is_zero) = t.invert();
+        (Fp12 { c0: self.c0 * t_inv, c1: -(self.c1 * t_inv) }, is_zero)
+    }
+
+    // Effectively self^(N^power); Both Fp6 coefficients go through their own Frobenius map, and c1
+    // picks up the tabulated multiple of w; Past power 6 the map continues with a conjugation
+    #[must_use]
+    pub fn frobenius_map(&self, power: usize) -> Fp12 {
+        let k = power % 12;
+        if k >= 6 {
+            return self.frobenius_map(k - 6).conjugate();
+        }
+        Fp12 {
+            c0: self.c0.frobenius_map(k),
+            c1: self.c1.frobenius_map(k).mul_by_0(&FROBENIUS_COEFF_C1[k]),
+        }
+    }
+
+    #[must_use]
+    pub fn ct_eq(&self, other: &Fp12) -> Choice {
+        self.c0.ct_eq(&other.c0) & self.c1.ct_eq(&other.c1)
+    }
+
+    #[must_use]
+    pub fn ct_is_zero(&self) -> Choice {
+        self.ct_eq(&Fp12::ZERO)
+    }
+
+    // Returns b when choice is true, otherwise a
+    #[must_use]
+    pub fn conditional_select(a: &Fp12, b: &Fp12, choice: Choice) -> Fp12 {
+        Fp12 {
+            c0: Fp6::conditional_select(&a.c0, &b.c0, choice),
+            c1: Fp6::conditional_select(&a.c1, &b.c1, choice),
+        }
+    }
+}
+
+// The four Fp2 coefficients g1 = c0.c1, g2 = c0.c2, g3 = c1.c0 and g5 = c1.c2 of an element of the
+// cyclotomic subgroup, which determine the remaining g0 = c0.c0 and g4 = c1.c1 (Karabina, "Squaring
+// in cyclotomic subgroups", 2013); Squaring stays compressed, so a run of squarings in the final
+// exponentiation pays for only one decompression, with its inversion, at the end; The paper numbers
+// the coefficients differently: its kept g2, g3, g4, g5 are g3, g2, g1, g5 here, and its recovered
+// g1 is g4 here (g0 is g0 in both)
+#[derive(Default, Clone, Copy, Debug, PartialEq)] // PartialEq is not constant time
+pub struct Fp12Compressed {
+    pub g1: Fp2,
+    pub g2: Fp2,
+    pub g3: Fp2,
+    pub g5: Fp2,
+}
+
+impl Fp12Compressed {
+    // Squaring in compressed form, for six Fp2 squarings (and no multiplications)
+    #[must_use]
+    pub fn square(&self) -> Fp12Compressed {
+        let (g1, g2, g3, g5) = (self.g1, self.g2, self.g3, self.g5);
+        let (g1_sqr, g2_sqr, g3_sqr, g5_sqr) = (g1.square(), g2.square(), g3.square(), g5.square());
+        let g1g5_twice = (g1 + g5).square() - g1_sqr - g5_sqr;
+        let g2g3_twice = (g2 + g3).square() - g2_sqr - g3_sqr;
+        let triple = |x: Fp2| x + x + x;
+
+        // With E = 1 + u: 3*(g1^2 + E*g5^2) - 2*g2, 3*(g3^2 + E*g2^2) - 2*g1, 3*E*2*g1*g5 + 2*g3
+        // and 3*2*g2*g3 + 2*g5
+        let g2_new = triple(g1_sqr + g5_sqr.mul_by_nonresidue()) - g2 - g2;
+        let g1_new = triple(g3_sqr + g2_sqr.mul_by_nonresidue()) - g1 - g1;
+        let g3_new = triple(g1g5_twice.mul_by_nonresidue()) + g3 + g3;
+        let g5_new = triple(g2g3_twice) + g5 + g5;
+        Fp12Compressed { g1: g1_new, g2: g2_new, g3: g3_new, g5: g5_new }
+    }
+
+    // g4 is numerator / denominator: (E*g5^2 + 3*g1^2 - 2*g2) / (4*g3), or 2*g1*g5 / g2 when g3 is
+    // zero; Selected in constant time
+    fn g4_fraction(&self) -> (Fp2, Fp2) {
+        let g1_sqr = self.g1.square();
+        let numerator =
+            self.g5.square().mul_by_nonresidue() + g1_sqr + g1_sqr + g1_sqr - self.g2 - self.g2;
+        let g3_twice = self.g3 + self.g3;
+        let g1g5 = self.g1 * self.g5;
+        let g3_is_zero = self.g3.ct_is_zero();
+        (
+            Fp2::conditional_select(&numerator, &(g1g5 + g1g5), g3_is_zero),
+            Fp2::conditional_select(&(g3_twice + g3_twice), &self.g2, g3_is_zero),
+        )
+    }
+
+    // Given g4, g0 = E*(2*g4^2 + g3*g5 - 3*g1*g2) + 1
+    fn with_g4(&self, g4: Fp2) -> Fp12 {
+        let g1g2 = self.g1 * self.g2;
+        let g4_sqr = g4.square();
+        let g0 = (g4_sqr + g4_sqr + self.g3 * self.g5 - g1g2 - g1g2 - g1g2).mul_by_nonresidue()
+            + Fp2::ONE;
+        Fp12 { c0: Fp6::new(g0, self.g1, self.g2), c1: Fp6::new(self.g3, g4, self.g5) }
+    }
+
+    // Recover the full element, at the cost of one Fp2 inversion
+    #[must_use]
+    pub fn decompress(&self) -> Fp12 {
+        let (numerator, denominator) = self.g4_fraction();
+        self.with_g4(numerator * denominator.invert().0)
+    }
+
+    // Decompress every element into result, sharing a single Fp2 inversion via Montgomery's
+    // simultaneous inversion trick as in fe_batch_inv; The result slots double as scratch space, so
+    // this needs no allocation; Panics unless both slices have the same length
+    #[allow(clippy::missing_panics_doc)]
+    pub fn batch_decompress(compressed: &[Fp12Compressed], result: &mut [Fp12]) {
+        assert_eq!(compressed.len(), result.len());
+        // Park each numerator, denominator and running product of the denominators before it in
+        // the result; Zero denominators (for g4 = 0) are stood in for by one
+        let mut acc = Fp2::ONE;
+        for (slot, x) in result.iter_mut().zip(compressed.iter()) {
+            let (numerator, denominator) = x.g4_fraction();
+            let nonzero =
+                Fp2::conditional_select(&denominator, &Fp2::ONE, denominator.ct_is_zero());
+            *slot = Fp12 {
+                c0: Fp6::new(acc, denominator, Fp2::ZERO),
+                c1: Fp6::new(numerator, nonzero, Fp2::ZERO),
+            };
+            acc *= nonzero;
+        }
+
+        let mut acc_inv = acc.invert().0; // acc is never zero
+        for (slot, x) in result.iter_mut().zip(compressed.iter()).rev() {
+            let (prefix, denominator) = (slot.c0.c0, slot.c0.c1);
+            let (numerator, nonzero) = (slot.c1.c0, slot.c1.c1);
+            let denominator_inv =
+                Fp2::conditional_select(&(acc_inv * prefix), &Fp2::ZERO, denominator.ct_is_zero());
+            acc_inv *= nonzero;
+            *slot = x.with_g4(numerator * denominator_inv);
+        }
+    }
+}
+
+impl Add for Fp12 {
+    type Output = Fp12;
+    fn add(self, rhs: Fp12) -> Fp12 {
+        Fp12 { c0: self.c0 + rhs.c0, c1: self.c1 + rhs.c1 }
+    }
+}
+
+impl Sub for Fp12 {
+    type Output = Fp12;
+    fn sub(self, rhs: Fp12) -> Fp12 {
+        Fp12 { c0: self.c0 - rhs.c0, c1: self.c1 - rhs.c1 }
+    }
+}
+
+// Karatsuba: three multiplications in Fp6 rather than four
+impl Mul for Fp12 {
+    type Output = Fp12;
+    fn mul(self, rhs: Fp12) -> Fp12 {
+        let v0 = self.c0 * rhs.c0;
+        let v1 = self.c1 * rhs.c1;
+        Fp12 {
+            c0: v0 + v1.mul_by_nonresidue(),
+            c1: (self.c0 + self.c1) * (rhs.c0 + rhs.c1) - v0 - v1,
+        }
+    }
+}
+
+impl Neg for Fp12 {
+    type Output = Fp12;
+    fn neg(self) -> Fp12 {
+        Fp12 { c0: -self.c0, c1: -self.c1 }
+    }
+}
+
+impl_field_ops!(Fp12);
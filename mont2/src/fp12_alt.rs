@@ -0,0 +1,118 @@
+// Copyright 2021 Eric Schorn; Licensed under the 3-Clause BSD License.
+
+use crate::arith::Choice;
+use crate::fp12::Fp12;
+use crate::fp4::Fp4;
+use crate::fp6::Fp6;
+use core::ops::{Add, Mul, Neg, Sub};
+
+// BLS12-381 element c0 + c1*t + c2*t^2 of Fp12 = Fp4[t]/(t^3 - s), an alternative to the standard
+// Fp2 -> Fp6 -> Fp12 tower kept for comparison; Both adjoin a sixth root of 1 + u to Fp2, as t = w
+// and s = w^3, so the isomorphism merely reorders the six Fp2 coefficients
+#[derive(Default, Clone, Copy, Debug, PartialEq)] // PartialEq is not constant time; see ct_eq
+pub struct Fp12Alt {
+    pub c0: Fp4,
+    pub c1: Fp4,
+    pub c2: Fp4,
+}
+
+// With w^2 = v, the standard basis 1, v, v^2, w, v*w, v^2*w is w^0, w^2, w^4, w^1, w^3, w^5, while
+// this one, 1, s, t, s*t, t^2, s*t^2, is w^0, w^3, w^1, w^4, w^2, w^5
+impl From<Fp12> for Fp12Alt {
+    fn from(f: Fp12) -> Fp12Alt {
+        Fp12Alt {
+            c0: Fp4::new(f.c0.c0, f.c1.c1),
+            c1: Fp4::new(f.c1.c0, f.c0.c2),
+            c2: Fp4::new(f.c0.c1, f.c1.c2),
+        }
+    }
+}
+
+impl From<Fp12Alt> for Fp12 {
+    fn from(f: Fp12Alt) -> Fp12 {
+        Fp12::new(Fp6::new(f.c0.c0, f.c2.c0, f.c1.c1), Fp6::new(f.c1.c0, f.c0.c1, f.c2.c1))
+    }
+}
+
+impl Fp12Alt {
+    pub const ZERO: Fp12Alt = Fp12Alt { c0: Fp4::ZERO, c1: Fp4::ZERO, c2: Fp4::ZERO };
+    pub const ONE: Fp12Alt = Fp12Alt { c0: Fp4::ONE, c1: Fp4::ZERO, c2: Fp4::ZERO };
+
+    #[must_use]
+    pub fn new(c0: Fp4, c1: Fp4, c2: Fp4) -> Fp12Alt {
+        Fp12Alt { c0, c1, c2 }
+    }
+
+    // Chung-Hasan SQR2 over Fp4, exactly as Fp6::square is over Fp2
+    #[must_use]
+    pub fn square(&self) -> Fp12Alt {
+        let s0 = self.c0.square();
+        let c0c1 = self.c0 * self.c1;
+        let s1 = c0c1 + c0c1;
+        let s2 = (self.c0 - self.c1 + self.c2).square();
+        let c1c2 = self.c1 * self.c2;
+        let s3 = c1c2 + c1c2;
+        let s4 = self.c2.square();
+        Fp12Alt {
+            c0: s0 + s3.mul_by_nonresidue(),
+            c1: s1 + s4.mul_by_nonresidue(),
+            c2: s1 + s2 + s3 - s0 - s4,
+        }
+    }
+
+    // Granger-Scott squaring for elements of the cyclotomic subgroup, which this tower expresses
+    // directly: (3*c0^2 - 2*conj(c0)) + (3*s*c2^2 + 2*conj(c1))*t + (3*c1^2 - 2*conj(c2))*t^2
+    #[must_use]
+    pub fn cyclotomic_square(&self) -> Fp12Alt {
+        let minus = |t: Fp4, z: Fp4| (t - z) + (t - z) + t;
+        let plus = |t: Fp4, z: Fp4| (t + z) + (t + z) + t;
+        Fp12Alt {
+            c0: minus(self.c0.square(), self.c0.conjugate()),
+            c1: plus(self.c2.square().mul_by_nonresidue(), self.c1.conjugate()),
+            c2: minus(self.c1.square(), self.c2.conjugate()),
+        }
+    }
+
+    #[must_use]
+    pub fn ct_eq(&self, other: &Fp12Alt) -> Choice {
+        self.c0.ct_eq(&other.c0) & self.c1.ct_eq(&other.c1) & self.c2.ct_eq(&other.c2)
+    }
+}
+
+impl Add for Fp12Alt {
+    type Output = Fp12Alt;
+    fn add(self, rhs: Fp12Alt) -> Fp12Alt {
+        Fp12Alt { c0: self.c0 + rhs.c0, c1: self.c1 + rhs.c1, c2: self.c2 + rhs.c2 }
+    }
+}
+
+impl Sub for Fp12Alt {
+    type Output = Fp12Alt;
+    fn sub(self, rhs: Fp12Alt) -> Fp12Alt {
+        Fp12Alt { c0: self.c0 - rhs.c0, c1: self.c1 - rhs.c1, c2: self.c2 - rhs.c2 }
+    }
+}
+
+// Karatsuba over three coefficients, for six multiplications in Fp4 (so 18 in Fp2, as in Fp12)
+impl Mul for Fp12Alt {
+    type Output = Fp12Alt;
+    fn mul(self, rhs: Fp12Alt) -> Fp12Alt {
+        let v0 = self.c0 * rhs.c0;
+        let v1 = self.c1 * rhs.c1;
+        let v2 = self.c2 * rhs.c2;
+        Fp12Alt {
+            c0: v0 + ((self.c1 + self.c2) * (rhs.c1 + rhs.c2) - v1 - v2).mul_by_nonresidue(),
+            c1: (self.c0 + self.c1) * (rhs.c0 + rhs.c1) - v0 - v1 + v2.mul_by_nonresidue(),
+            c2: (self.c0 + self.c2) * (rhs.c0 + rhs.c2) - v0 - v2 + v1,
+        }
+    }
+}
+
+impl Neg for Fp12Alt {
+    type Output = Fp12Alt;
+    fn neg(self) -> Fp12Alt {
+        Fp12Alt { c0: -self.c0, c1: -self.c1, c2: -self.c2 }
+    }
+}
+
+impl_field_ops!(Fp12Alt);
@@ -0,0 +1,119 @@
+// Copyright 2021 Eric Schorn; Licensed under the 3-Clause BSD License.
+
+use crate::arith::Choice;
+use crate::fp::Fp;
+use core::ops::{Add, Mul, Neg, Sub};
+
+// BLS12-381 quadratic extension element c0 + c1*u in Fp2 = Fp[u]/(u^2 + 1); As N = 3 mod 4, -1 is
+// not a square in Fp so this is a field
+#[derive(Default, Clone, Copy, Debug, PartialEq)] // PartialEq is not constant time; see ct_eq
+pub struct Fp2 {
+    pub c0: Fp,
+    pub c1: Fp,
+}
+
+impl Fp2 {
+    pub const ZERO: Fp2 = Fp2 { c0: Fp::ZERO, c1: Fp::ZERO };
+    pub const ONE: Fp2 = Fp2 { c0: Fp::ONE, c1: Fp::ZERO };
+
+    #[must_use]
+    pub fn new(c0: Fp, c1: Fp) -> Fp2 {
+        Fp2 { c0, c1 }
+    }
+
+    // Complex squaring: (c0 + c1*u)^2 = (c0 + c1)(c0 - c1) + 2*c0*c1*u, for two multiplications
+    #[must_use]
+    pub fn square(&self) -> Fp2 {
+        let c0c1 = self.c0 * self.c1;
+        Fp2 { c0: (self.c0 + self.c1) * (self.c0 - self.c1), c1: c0c1 + c0c1 }
+    }
+
+    // Effectively c0 - c1*u
+    #[must_use]
+    pub fn conjugate(&self) -> Fp2 {
+        Fp2 { c0: self.c0, c1: -self.c1 }
+    }
+
+    // Multiplication by the non-residue 1 + u used to build the higher extensions, which costs
+    // only an addition and a subtraction: (c0 - c1) + (c0 + c1)*u
+    #[must_use]
+    pub fn mul_by_nonresidue(&self) -> Fp2 {
+        Fp2 { c0: self.c0 - self.c1, c1: self.c0 + self.c1 }
+    }
+
+    // The norm c0^2 + c1^2 = self * conjugate, which lies in Fp
+    #[must_use]
+    pub fn norm(&self) -> Fp {
+        self.c0.square() + self.c1.square()
+    }
+
+    // Returns the inverse conjugate / norm along with a Choice that is true when self is zero (and
+    // so is the result); The norm of a non-zero element is never zero
+    #[must_use]
+    pub fn invert(&self) -> (Fp2, Choice) {
+        let (norm_inv, is_zero) = self.norm().invert();
+        (Fp2 { c0: self.c0 * norm_inv, c1: -(self.c1 * norm_inv) }, is_zero)
+    }
+
+    // Effectively self^(N^power); As u^N = -u, odd powers conjugate and even powers do nothing
+    #[must_use]
+    pub fn frobenius_map(&self, power: usize) -> Fp2 {
+        if power % 2 == 1 {
+            self.conjugate()
+        } else {
+            *self
+        }
+    }
+
+    #[must_use]
+    pub fn ct_eq(&self, other: &Fp2) -> Choice {
+        self.c0.ct_eq(&other.c0) & self.c1.ct_eq(&other.c1)
+    }
+
+    #[must_use]
+    pub fn ct_is_zero(&self) -> Choice {
+        self.ct_eq(&Fp2::ZERO)
+    }
+
+    // Returns b when choice is true, otherwise a
+    #[must_use]
+    pub fn conditional_select(a: &Fp2, b: &Fp2, choice: Choice) -> Fp2 {
+        Fp2 {
+            c0: Fp::conditional_select(&a.c0, &b.c0, choice),
+            c1: Fp::conditional_select(&a.c1, &b.c1, choice),
+        }
+    }
+}
+
+impl Add for Fp2 {
+    type Output = Fp2;
+    fn add(self, rhs: Fp2) -> Fp2 {
+        Fp2 { c0: self.c0 + rhs.c0, c1: self.c1 + rhs.c1 }
+    }
+}
+
+impl Sub for Fp2 {
+    type Output = Fp2;
+    fn sub(self, rhs: Fp2) -> Fp2 {
+        Fp2 { c0: self.c0 - rhs.c0, c1: self.c1 - rhs.c1 }
+    }
+}
+
+// Karatsuba: three multiplications rather than four, with c1 = (a0 + a1)(b0 + b1) - a0*b0 - a1*b1
+impl Mul for Fp2 {
+    type Output = Fp2;
+    fn mul(self, rhs: Fp2) -> Fp2 {
+        let v0 = self.c0 * rhs.c0;
+        let v1 = self.c1 * rhs.c1;
+        Fp2 { c0: v0 - v1, c1: (self.c0 + self.c1) * (rhs.c0 + rhs.c1) - v0 - v1 }
+    }
+}
+
+impl Neg for Fp2 {
+    type Output = Fp2;
+    fn neg(self) -> Fp2 {
+        Fp2 { c0: -self.c0, c1: -self.c1 }
+    }
+}
+
+impl_field_ops!(Fp2);
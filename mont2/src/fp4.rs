@@ -0,0 +1,95 @@
+// Copyright 2021 Eric Schorn; Licensed under the 3-Clause BSD License.
+
+use crate::arith::Choice;
+use crate::fp2::Fp2;
+use core::ops::{Add, Mul, Neg, Sub};
+
+// BLS12-381 quartic extension element c0 + c1*s in Fp4 = Fp2[s]/(s^2 - (1 + u)), the middle layer
+// of the alternative tower in fp12_alt.rs
+#[derive(Default, Clone, Copy, Debug, PartialEq)] // PartialEq is not constant time; see ct_eq
+pub struct Fp4 {
+    pub c0: Fp2,
+    pub c1: Fp2,
+}
+
+impl Fp4 {
+    pub const ZERO: Fp4 = Fp4 { c0: Fp2::ZERO, c1: Fp2::ZERO };
+    pub const ONE: Fp4 = Fp4 { c0: Fp2::ONE, c1: Fp2::ZERO };
+
+    #[must_use]
+    pub fn new(c0: Fp2, c1: Fp2) -> Fp4 {
+        Fp4 { c0, c1 }
+    }
+
+    // Three squarings in Fp2: c0^2 + (1 + u)*c1^2 + ((c0 + c1)^2 - c0^2 - c1^2)*s
+    #[must_use]
+    pub fn square(&self) -> Fp4 {
+        let (c0_sqr, c1_sqr) = (self.c0.square(), self.c1.square());
+        Fp4 {
+            c0: c0_sqr + c1_sqr.mul_by_nonresidue(),
+            c1: (self.c0 + self.c1).square() - c0_sqr - c1_sqr,
+        }
+    }
+
+    // Effectively c0 - c1*s
+    #[must_use]
+    pub fn conjugate(&self) -> Fp4 {
+        Fp4 { c0: self.c0, c1: -self.c1 }
+    }
+
+    // Multiplication by s: (1 + u)*c1 + c0*s
+    #[must_use]
+    pub fn mul_by_nonresidue(&self) -> Fp4 {
+        Fp4 { c0: self.c1.mul_by_nonresidue(), c1: self.c0 }
+    }
+
+    // Returns the inverse conjugate / (c0^2 - (1 + u)*c1^2) along with a Choice that is true when
+    // self is zero (and so is the result)
+    #[must_use]
+    pub fn invert(&self) -> (Fp4, Choice) {
+        let norm = self.c0.square() - self.c1.square().mul_by_nonresidue();
+        let (norm_inv, is_zero) = norm.invert();
+        (Fp4 { c0: self.c0 * norm_inv, c1: -(self.c1 * norm_inv) }, is_zero)
+    }
+
+    #[must_use]
+    pub fn ct_eq(&self, other: &Fp4) -> Choice {
+        self.c0.ct_eq(&other.c0) & self.c1.ct_eq(&other.c1)
+    }
+}
+
+impl Add for Fp4 {
+    type Output = Fp4;
+    fn add(self, rhs: Fp4) -> Fp4 {
+        Fp4 { c0: self.c0 + rhs.c0, c1: self.c1 + rhs.c1 }
+    }
+}
+
+impl Sub for Fp4 {
+    type Output = Fp4;
+    fn sub(self, rhs: Fp4) -> Fp4 {
+        Fp4 { c0: self.c0 - rhs.c0, c1: self.c1 - rhs.c1 }
+    }
+}
+
+// Karatsuba: three multiplications in Fp2 rather than four
+impl Mul for Fp4 {
+    type Output = Fp4;
+    fn mul(self, rhs: Fp4) -> Fp4 {
+        let v0 = self.c0 * rhs.c0;
+        let v1 = self.c1 * rhs.c1;
+        Fp4 {
+            c0: v0 + v1.mul_by_nonresidue(),
+            c1: (self.c0 + self.c1) * (rhs.c0 + rhs.c1) - v0 - v1,
+        }
+    }
+}
+
+impl Neg for Fp4 {
+    type Output = Fp4;
+    fn neg(self) -> Fp4 {
+        Fp4 { c0: -self.c0, c1: -self.c1 }
+    }
+}
+
+impl_field_ops!(Fp4);
@@ -0,0 +1,203 @@
+// Copyright 2021 Eric Schorn; Licensed under the 3-Clause BSD License.
+
+use crate::arith::Choice;
+use crate::fp::Fp;
+use crate::fp2::Fp2;
+use core::ops::{Add, Mul, Neg, Sub};
+
+// BLS12-381 cubic extension element c0 + c1*v + c2*v^2 in Fp6 = Fp2[v]/(v^3 - (1 + u)), where
+// multiplying by v^3 is Fp2::mul_by_nonresidue
+#[derive(Default, Clone, Copy, Debug, PartialEq)] // PartialEq is not constant time; see ct_eq
+pub struct Fp6 {
+    pub c0: Fp2,
+    pub c1: Fp2,
+    pub c2: Fp2,
+}
+
+// The Fp values appearing in the Frobenius coefficients below, in Montgomery form
+#[rustfmt::skip]
+const FROB_A: Fp = Fp::from_mont_unchecked([
+    0xcd03_c9e4_8671_f071, 0x5dab_2246_1fcd_a5d2, 0x5870_42af_d385_1b95,
+    0x8eb6_0ebe_01ba_cb9e, 0x03f9_7d6e_83d0_50d2, 0x18f0_2065_5463_8741,
+]);
+#[rustfmt::skip]
+pub(crate) const FROB_B: Fp = Fp::from_mont_unchecked([
+    0x30f1_361b_798a_64e8, 0xf3b8_ddab_7ece_5a2a, 0x16a8_ca3a_c615_77f7,
+    0xc26a_2ff8_74fd_029b, 0x3636_b766_6070_1c6e, 0x051b_a4ab_241b_6160,
+]);
+#[rustfmt::skip]
+const FROB_C: Fp = Fp::from_mont_unchecked([
+    0x890d_c9e4_8675_45c3, 0x2af3_2253_3285_a5d5, 0x5088_0866_309b_7e2c,
+    0xa20d_1b8c_7e88_1024, 0x14e4_f04f_e2db_9068, 0x14e5_6d3f_1564_853a,
+]);
+#[rustfmt::skip]
+const FROB_D: Fp = Fp::from_mont_unchecked([
+    0x43f5_ffff_fffc_aaae, 0x32b7_fff2_ed47_fffd, 0x07e8_3a49_a2e9_9d69,
+    0xeca8_f331_8332_bb7a, 0xef14_8d1e_a0f4_c069, 0x040a_b326_3eff_0206,
+]);
+#[rustfmt::skip]
+pub(crate) const FROB_E: Fp = Fp::from_mont_unchecked([
+    0xecfb_361b_798d_ba3a, 0xc100_ddb8_9186_5a2c, 0x0ec0_8ff1_232b_da8e,
+    0xd5c1_3cc6_f1ca_4721, 0x4722_2a47_bf7b_5c04, 0x0110_f184_e51c_5f59,
+]);
+
+// (1 + u)^((N^k - 1)/3) for k = 0..5, which frobenius_map(k) multiplies into c1
+pub(crate) const FROBENIUS_COEFF_C1: [Fp2; 6] = [
+    Fp2 { c0: Fp::ONE, c1: Fp::ZERO },
+    Fp2 { c0: Fp::ZERO, c1: FROB_A },
+    Fp2 { c0: FROB_B, c1: Fp::ZERO },
+    Fp2 { c0: Fp::ZERO, c1: Fp::ONE },
+    Fp2 { c0: FROB_A, c1: Fp::ZERO },
+    Fp2 { c0: Fp::ZERO, c1: FROB_B },
+];
+
+// (1 + u)^(2(N^k - 1)/3) for k = 0..5, which frobenius_map(k) multiplies into c2
+pub(crate) const FROBENIUS_COEFF_C2: [Fp2; 6] = [
+    Fp2 { c0: Fp::ONE, c1: Fp::ZERO },
+    Fp2 { c0: FROB_C, c1: Fp::ZERO },
+    Fp2 { c0: FROB_A, c1: Fp::ZERO },
+    Fp2 { c0: FROB_D, c1: Fp::ZERO },
+    Fp2 { c0: FROB_B, c1: Fp::ZERO },
+    Fp2 { c0: FROB_E, c1: Fp::ZERO },
+];
+
+impl Fp6 {
+    pub const ZERO: Fp6 = Fp6 { c0: Fp2::ZERO, c1: Fp2::ZERO, c2: Fp2::ZERO };
+    pub const ONE: Fp6 = Fp6 { c0: Fp2::ONE, c1: Fp2::ZERO, c2: Fp2::ZERO };
+
+    #[must_use]
+    pub fn new(c0: Fp2, c1: Fp2, c2: Fp2) -> Fp6 {
+        Fp6 { c0, c1, c2 }
+    }
+
+    // Chung-Hasan SQR2, with two squarings and three multiplications in Fp2:
+    // s0 = c0^2, s1 = 2*c0*c1, s2 = (c0 - c1 + c2)^2, s3 = 2*c1*c2, s4 = c2^2
+    #[must_use]
+    pub fn square(&self) -> Fp6 {
+        let s0 = self.c0.square();
+        let c0c1 = self.c0 * self.c1;
+        let s1 = c0c1 + c0c1;
+        let s2 = (self.c0 - self.c1 + self.c2).square();
+        let c1c2 = self.c1 * self.c2;
+        let s3 = c1c2 + c1c2;
+        let s4 = self.c2.square();
+        Fp6 {
+            c0: s0 + s3.mul_by_nonresidue(),
+            c1: s1 + s4.mul_by_nonresidue(),
+            c2: s1 + s2 + s3 - s0 - s4,
+        }
+    }
+
+    // Multiplication by v, which rotates the coefficients: (1 + u)*c2 + c0*v + c1*v^2
+    #[must_use]
+    pub fn mul_by_nonresidue(&self) -> Fp6 {
+        Fp6 { c0: self.c2.mul_by_nonresidue(), c1: self.c0, c2: self.c1 }
+    }
+
+    // Multiplication by the sparse element b0, i.e. scaling each coefficient, for three
+    // multiplications in Fp2
+    #[must_use]
+    pub fn mul_by_0(&self, b0: &Fp2) -> Fp6 {
+        Fp6 { c0: self.c0 * b0, c1: self.c1 * b0, c2: self.c2 * b0 }
+    }
+
+    // Multiplication by the sparse element b1*v, for three multiplications in Fp2
+    #[must_use]
+    pub fn mul_by_1(&self, b1: &Fp2) -> Fp6 {
+        Fp6 { c0: (self.c2 * b1).mul_by_nonresidue(), c1: self.c0 * b1, c2: self.c1 * b1 }
+    }
+
+    // Multiplication by the sparse element b0 + b1*v, for five multiplications in Fp2
+    #[must_use]
+    pub fn mul_by_01(&self, b0: &Fp2, b1: &Fp2) -> Fp6 {
+        let v0 = self.c0 * b0;
+        let v1 = self.c1 * b1;
+        Fp6 {
+            c0: (self.c2 * b1).mul_by_nonresidue() + v0,
+            c1: (self.c0 + self.c1) * (b0 + b1) - v0 - v1,
+            c2: self.c2 * b0 + v1,
+        }
+    }
+
+    // Returns the inverse along with a Choice that is true when self is zero (and so is the
+    // result); The adjugate t0 + t1*v + t2*v^2 times self lies in Fp2, leaving one Fp2 inversion
+    #[must_use]
+    pub fn invert(&self) -> (Fp6, Choice) {
+        let t0 = self.c0.square() - (self.c1 * self.c2).mul_by_nonresidue();
+        let t1 = self.c2.square().mul_by_nonresidue() - self.c0 * self.c1;
+        let t2 = self.c1.square() - self.c0 * self.c2;
+        let det = self.c0 * t0 + (self.c2 * t1 + self.c1 * t2).mul_by_nonresidue();
+        let (det_inv, is_zero) = det.invert();
+        (Fp6 { c0: t0 * det_inv, c1: t1 * det_inv, c2: t2 * det_inv }, is_zero)
+    }
+
+    // Effectively self^(N^power); Each Fp2 coefficient goes through its own Frobenius map, then
+    // v^(N^k) = (1 + u)^((N^k - 1)/3) * v picks up the tabulated constants
+    #[must_use]
+    pub fn frobenius_map(&self, power: usize) -> Fp6 {
+        let k = power % 6;
+        Fp6 {
+            c0: self.c0.frobenius_map(k),
+            c1: self.c1.frobenius_map(k) * FROBENIUS_COEFF_C1[k],
+            c2: self.c2.frobenius_map(k) * FROBENIUS_COEFF_C2[k],
+        }
+    }
+
+    #[must_use]
+    pub fn ct_eq(&self, other: &Fp6) -> Choice {
+        self.c0.ct_eq(&other.c0) & self.c1.ct_eq(&other.c1) & self.c2.ct_eq(&other.c2)
+    }
+
+    #[must_use]
+    pub fn ct_is_zero(&self) -> Choice {
+        self.ct_eq(&Fp6::ZERO)
+    }
+
+    // Returns b when choice is true, otherwise a
+    #[must_use]
+    pub fn conditional_select(a: &Fp6, b: &Fp6, choice: Choice) -> Fp6 {
+        Fp6 {
+            c0: Fp2::conditional_select(&a.c0, &b.c0, choice),
+            c1: Fp2::conditional_select(&a.c1, &b.c1, choice),
+            c2: Fp2::conditional_select(&a.c2, &b.c2, choice),
+        }
+    }
+}
+
+impl Add for Fp6 {
+    type Output = Fp6;
+    fn add(self, rhs: Fp6) -> Fp6 {
+        Fp6 { c0: self.c0 + rhs.c0, c1: self.c1 + rhs.c1, c2: self.c2 + rhs.c2 }
+    }
+}
+
+impl Sub for Fp6 {
+    type Output = Fp6;
+    fn sub(self, rhs: Fp6) -> Fp6 {
+        Fp6 { c0: self.c0 - rhs.c0, c1: self.c1 - rhs.c1, c2: self.c2 - rhs.c2 }
+    }
+}
+
+// Karatsuba over three coefficients, for six multiplications in Fp2 rather than nine
+impl Mul for Fp6 {
+    type Output = Fp6;
+    fn mul(self, rhs: Fp6) -> Fp6 {
+        let v0 = self.c0 * rhs.c0;
+        let v1 = self.c1 * rhs.c1;
+        let v2 = self.c2 * rhs.c2;
+        Fp6 {
+            c0: v0 + ((self.c1 + self.c2) * (rhs.c1 + rhs.c2) - v1 - v2).mul_by_nonresidue(),
+            c1: (self.c0 + self.c1) * (rhs.c0 + rhs.c1) - v0 - v1 + v2.mul_by_nonresidue(),
+            c2: (self.c0 + self.c2) * (rhs.c0 + rhs.c2) - v0 - v2 + v1,
+        }
+    }
+}
+
+impl Neg for Fp6 {
+    type Output = Fp6;
+    fn neg(self) -> Fp6 {
+        Fp6 { c0: -self.c0, c1: -self.c1, c2: -self.c2 }
+    }
+}
+
+impl_field_ops!(Fp6);
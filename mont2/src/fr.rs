@@ -0,0 +1,165 @@
+// Copyright 2021 Eric Schorn; Licensed under the 3-Clause BSD License.
+
+use crate::arith::{Choice, FieldError};
+use crate::field::{self, BLS12_381_FR};
+use core::ops::{Add, Mul, Neg, Sub};
+
+// Length in bytes of a canonical scalar encoding
+pub const FR_BYTES: usize = 32;
+
+// BLS12-381 scalar field element modulo the 255-bit group order r, always held fully reduced in
+// Montgomery form with R = 2^256
+#[derive(Default, Clone, Copy, Debug, PartialEq)] // PartialEq is not constant time; see ct_eq
+pub struct Fr([u64; 4]);
+
+impl Fr {
+    pub const ZERO: Fr = Fr([0; 4]);
+    pub const ONE: Fr = Fr(BLS12_381_FR.r_mod_n);
+
+    // Any u64 is already canonical
+    #[must_use]
+    pub fn from_u64(x: u64) -> Fr {
+        let mut result = [0_u64; 4];
+        BLS12_381_FR.to_mont(&mut result, &[x, 0, 0, 0]);
+        Fr(result)
+    }
+
+    // Canonical value, least significant limb first; Refuses anything >= r
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_norm(norm: &[u64; 4]) -> Result<Fr, FieldError> {
+        if !BLS12_381_FR.is_canonical(norm) {
+            return Err(FieldError::NotCanonical);
+        }
+        let mut result = [0_u64; 4];
+        BLS12_381_FR.to_mont(&mut result, norm);
+        Ok(Fr(result))
+    }
+
+    // Any 512-bit value, least significant limb first, reduced modulo r; Uniformly random input
+    // gives a scalar with a bias of around 2^-257
+    #[must_use]
+    pub fn from_wide(wide: &[u64; 8]) -> Fr {
+        let (mut lo, mut hi) = ([0_u64; 4], [0_u64; 4]);
+        lo.copy_from_slice(&wide[0..4]);
+        hi.copy_from_slice(&wide[4..8]);
+        let mut result = [0_u64; 4];
+        BLS12_381_FR.reduce_wide(&mut result, &lo, &hi);
+        Fr(result)
+    }
+
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_bytes_be(bytes: &[u8; FR_BYTES]) -> Result<Fr, FieldError> {
+        let mut le_bytes = *bytes;
+        le_bytes.reverse();
+        Fr::from_bytes_le(&le_bytes)
+    }
+
+    #[allow(clippy::missing_errors_doc)]
+    pub fn from_bytes_le(bytes: &[u8; FR_BYTES]) -> Result<Fr, FieldError> {
+        let mut norm = [0_u64; 4];
+        for (limb, chunk) in norm.iter_mut().zip(bytes.chunks(8)) {
+            let mut limb_bytes = [0_u8; 8];
+            limb_bytes.copy_from_slice(chunk);
+            *limb = u64::from_le_bytes(limb_bytes);
+        }
+        Fr::from_norm(&norm)
+    }
+
+    // Canonical value, least significant limb first
+    #[must_use]
+    pub fn to_norm(&self) -> [u64; 4] {
+        let mut norm = [0_u64; 4];
+        BLS12_381_FR.to_norm(&mut norm, &self.0);
+        norm
+    }
+
+    #[must_use]
+    pub fn to_bytes_be(&self) -> [u8; FR_BYTES] {
+        let mut bytes = self.to_bytes_le();
+        bytes.reverse();
+        bytes
+    }
+
+    #[must_use]
+    pub fn to_bytes_le(&self) -> [u8; FR_BYTES] {
+        let mut bytes = [0_u8; FR_BYTES];
+        for (chunk, limb) in bytes.chunks_mut(8).zip(self.to_norm().iter()) {
+            chunk.copy_from_slice(&limb.to_le_bytes());
+        }
+        bytes
+    }
+
+    // The underlying Montgomery form limbs, for use with BLS12_381_FR
+    #[must_use]
+    pub fn as_mont(&self) -> &[u64; 4] {
+        &self.0
+    }
+
+    // Not specialised: BLS12_381_FR has no dedicated squaring, so this is just mont_mul(self, self)
+    #[must_use]
+    pub fn square(&self) -> Fr {
+        let mut result = [0_u64; 4];
+        BLS12_381_FR.mont_mul(&mut result, &self.0, &self.0);
+        Fr(result)
+    }
+
+    // Returns the inverse along with a Choice that is true when self is zero (and so is the result)
+    #[must_use]
+    pub fn invert(&self) -> (Fr, Choice) {
+        let mut result = [0_u64; 4];
+        let is_zero = BLS12_381_FR.inv(&mut result, &self.0);
+        (Fr(result), is_zero)
+    }
+
+    #[must_use]
+    pub fn ct_eq(&self, other: &Fr) -> Choice {
+        field::ct_eq(&self.0, &other.0)
+    }
+
+    #[must_use]
+    pub fn ct_is_zero(&self) -> Choice {
+        self.ct_eq(&Fr::ZERO)
+    }
+
+    // Returns b when choice is true, otherwise a
+    #[must_use]
+    pub fn conditional_select(a: &Fr, b: &Fr, choice: Choice) -> Fr {
+        Fr(field::conditional_select(&a.0, &b.0, choice))
+    }
+}
+
+impl Add for Fr {
+    type Output = Fr;
+    fn add(self, rhs: Fr) -> Fr {
+        let mut result = [0_u64; 4];
+        BLS12_381_FR.add(&mut result, &self.0, &rhs.0);
+        Fr(result)
+    }
+}
+
+impl Sub for Fr {
+    type Output = Fr;
+    fn sub(self, rhs: Fr) -> Fr {
+        let mut result = [0_u64; 4];
+        BLS12_381_FR.sub(&mut result, &self.0, &rhs.0);
+        Fr(result)
+    }
+}
+
+impl Mul for Fr {
+    type Output = Fr;
+    fn mul(self, rhs: Fr) -> Fr {
+        let mut result = [0_u64; 4];
+        BLS12_381_FR.mont_mul(&mut result, &self.0, &rhs.0);
+        Fr(result)
+    }
+}
+
+impl Neg for Fr {
+    type Output = Fr;
+    fn neg(self) -> Fr {
+        Fr::ZERO - self
+    }
+}
+
+impl_field_ops!(Fr);
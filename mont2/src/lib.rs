@@ -1,17 +1,44 @@
 // Copyright 2021 Eric Schorn; Licensed under the 3-Clause BSD License.
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)] // Tests always run with std
 #![deny(clippy::all)]
 #![deny(clippy::pedantic)]
 #![deny(clippy::cargo)]
 
+#[macro_use]
+mod macros;
+
 pub mod arith;
+pub mod dispatch;
+pub mod field;
+pub mod fp;
+pub mod fp12;
+pub mod fp12_alt;
+pub mod fp2;
+pub mod fp4;
+pub mod fp6;
+pub mod fr;
+mod moduli;
+
+pub use dispatch::{fe_mont_mul, fe_mont_sqr};
 
 // Benchmark with `cargo bench` but prefer the following:
 //   RUSTFLAGS="--emit asm -C target-cpu=native" cargo bench
 //   RUSTFLAGS="--emit asm -C target-feature=+bmi2" cargo bench
 
+#[cfg(all(feature = "asm", target_arch = "x86_64"))]
 extern "C" {
     pub fn fe_mont_mul_asm(result: &mut u64, a: &u64, b: &u64);
+    pub fn fe_mont_sqr_asm(result: &mut u64, a: &u64);
+
+    // Generated by build.rs for the moduli in moduli.rs; Reach them via the field.rs instances
+    pub fn mont_mul_asm_bls12_381_r(result: &mut u64, a: &u64, b: &u64);
+    pub fn mont_mul_asm_bn254_q(result: &mut u64, a: &u64, b: &u64);
+    pub fn mont_mul_asm_bn254_r(result: &mut u64, a: &u64, b: &u64);
+    pub fn mont_mul_asm_bls12_377_q(result: &mut u64, a: &u64, b: &u64);
+    pub fn mont_mul_asm_bls12_377_r(result: &mut u64, a: &u64, b: &u64);
+    pub fn mont_mul_asm_p448(result: &mut u64, a: &u64, b: &u64);
+    pub fn mont_mul_asm_p512(result: &mut u64, a: &u64, b: &u64);
 }
 
 #[cfg(test)]
@@ -20,15 +47,33 @@ extern crate lazy_static;
 
 #[cfg(test)]
 mod tests {
+    #[cfg(all(feature = "intrinsics", target_arch = "x86_64"))]
+    use crate::arith::fe_mont_mul_intrinsics;
     use crate::arith::{
-        fe_add, fe_mont_mul, fe_mont_mul_intrinsics, fe_mont_mul_raw, fe_sub, fe_to_mont,
-        fe_to_norm, W6x64,
+        fe_add, fe_add_lazy, fe_inv, fe_is_square, fe_jacobi_vartime, fe_mont_mul_lazy,
+        fe_mont_mul_portable, fe_mont_mul_raw, fe_mont_sqr_portable, fe_pow, fe_pow_vartime,
+        fe_reduce, fe_sqrt, fe_sub, fe_sub_lazy, fe_to_mont, fe_to_norm, Choice, FieldError, W6x64,
+        FE_BYTES, N,
+    };
+    use crate::dispatch::{self, Backend};
+    use crate::field::{
+        correction, AsmMul, MontField, BLS12_377_FQ, BLS12_377_FR, BLS12_381_FP, BLS12_381_FR,
+        BN254_FQ, BN254_FR, P448_FP, P512_FP,
     };
-    use crate::fe_mont_mul_asm;
+    use crate::fp::Fp;
+    use crate::fp12::{self, Fp12, Fp12Compressed};
+    use crate::fp12_alt::Fp12Alt;
+    use crate::fp2::Fp2;
+    use crate::fp4::Fp4;
+    use crate::fp6::{Fp6, FROBENIUS_COEFF_C1, FROBENIUS_COEFF_C2};
+    use crate::fr::{Fr, FR_BYTES};
+    #[cfg(all(feature = "asm", target_arch = "x86_64"))]
+    use crate::{fe_mont_mul_asm, fe_mont_sqr_asm};
     use num_bigint::BigUint;
     use num_traits::Num;
     use rand::Rng;
     use std::convert::TryInto;
+    use std::iter::{Product, Sum};
 
     lazy_static! {
         static ref R: BigUint = BigUint::from(1_u64) << 384;
@@ -45,7 +90,8 @@ mod tests {
 
     fn big_to_6u64(x: &BigUint) -> [u64; 6] {
         let mut bytes = [0_u8; 48];
-        bytes[0..(((7 + x.bits()) / 8) as usize)].clone_from_slice(&x.to_bytes_le());
+        let le_bytes = x.to_bytes_le(); // Note: zero still yields one byte
+        bytes[0..le_bytes.len()].clone_from_slice(&le_bytes);
         let mut result = [0_u64; 6];
         for i in 0..6 {
             result[i] = u64::from_le_bytes(bytes[i * 8..(i + 1) * 8].try_into().unwrap());
@@ -53,6 +99,11 @@ mod tests {
         result
     }
 
+    fn six_u64_to_big(x: &[u64; 6]) -> BigUint {
+        let bytes: Vec<u8> = x.iter().flat_map(|limb| limb.to_le_bytes().to_vec()).collect();
+        BigUint::from_bytes_le(&bytes)
+    }
+
     lazy_static! { static ref R_INV: BigUint = BigUint::from_str_radix(
         "14fec701e8fb0ce9ed5e64273c4f538b1797ab1458a88de9343ea97914956dc87fe11274d898fafbf4d38259380b4820",
         16).unwrap();
@@ -131,7 +182,7 @@ mod tests {
             fe_to_mont(&mut a_mont, &big_to_6u64(&a_big));
             fe_to_mont(&mut b_mont, &big_to_6u64(&b_big));
 
-            fe_mont_mul(&mut actual_mont, &a_mont, &b_mont);
+            fe_mont_mul_portable(&mut actual_mont, &a_mont, &b_mont);
 
             mont_mul_biguint(&mut exp_mont, &(a_big * &(*R)), &(b_big * &(*R)));
             assert_eq!(big_to_6u64(&exp_mont), actual_mont.v);
@@ -139,6 +190,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(all(feature = "intrinsics", target_arch = "x86_64"))]
     fn test_fe_mont_mul_intrinsics() {
         let mut actual_mont = W6x64::default();
         let mut exp_mont = BigUint::default();
@@ -179,6 +231,7 @@ mod tests {
     }
 
     #[test]
+    #[cfg(all(feature = "asm", target_arch = "x86_64"))]
     fn test_fe_mont_mul_asm() {
         let mut actual_mont = W6x64::default();
         let mut exp_mont = BigUint::default();
@@ -199,4 +252,1307 @@ mod tests {
             assert_eq!(big_to_6u64(&exp_mont), actual_mont.v);
         }
     }
+
+    #[test]
+    fn test_fe_mont_sqr() {
+        let mut actual_mont = W6x64::default();
+        let mut exp_mont = BigUint::default();
+        let mut a_mont = W6x64::default();
+
+        for _i in 0..1_000_000 {
+            let a_big = rnd_big_mod_n();
+            fe_to_mont(&mut a_mont, &big_to_6u64(&a_big));
+
+            fe_mont_sqr_portable(&mut actual_mont, &a_mont);
+
+            mont_mul_biguint(&mut exp_mont, &(&a_big * &(*R)), &(a_big * &(*R)));
+            assert_eq!(big_to_6u64(&exp_mont), actual_mont.v);
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "asm", target_arch = "x86_64"))]
+    fn test_fe_mont_sqr_asm() {
+        let mut actual_mont = W6x64::default();
+        let mut exp_mont = BigUint::default();
+        let mut a_mont = W6x64::default();
+
+        for _i in 0..1_000_000 {
+            let a_big = rnd_big_mod_n();
+            fe_to_mont(&mut a_mont, &big_to_6u64(&a_big));
+
+            unsafe {
+                fe_mont_sqr_asm(&mut actual_mont.v[0], &a_mont.v[0]);
+            }
+
+            mont_mul_biguint(&mut exp_mont, &(&a_big * &(*R)), &(a_big * &(*R)));
+            assert_eq!(big_to_6u64(&exp_mont), actual_mont.v);
+        }
+
+        // The largest input maximizes every intermediate carry
+        let a_big = &(*MODULUS) - BigUint::from(1_u64);
+        fe_to_mont(&mut a_mont, &big_to_6u64(&a_big));
+        let mut expected = W6x64::default();
+        fe_mont_sqr_portable(&mut expected, &a_mont);
+        unsafe {
+            fe_mont_sqr_asm(&mut actual_mont.v[0], &a_mont.v[0]);
+        }
+        assert_eq!(expected, actual_mont);
+    }
+
+    #[test]
+    fn test_fe_inv() {
+        let mut actual_mont = W6x64::default();
+        let mut actual_norm = [0_u64; 6];
+        let mut a_mont = W6x64::default();
+        let exponent = &(*MODULUS) - BigUint::from(2_u64);
+
+        for _i in 0..100_000 {
+            let a_big = rnd_big_mod_n();
+            fe_to_mont(&mut a_mont, &big_to_6u64(&a_big));
+
+            let expected = a_big.modpow(&exponent, &MODULUS);
+            let is_zero = bool::from(fe_inv(&mut actual_mont, &a_mont));
+
+            fe_to_norm(&mut actual_norm, &actual_mont);
+            assert_eq!(big_to_6u64(&expected), actual_norm);
+            assert_eq!(a_big == BigUint::default(), is_zero);
+        }
+
+        // Zero has no inverse; expect the indicator and a zero result
+        let is_zero = bool::from(fe_inv(&mut actual_mont, &W6x64::default()));
+        assert!(is_zero);
+        assert_eq!(W6x64::default(), actual_mont);
+
+        // Both ends of the range, i.e. 1 and N-1
+        for a_big in &[BigUint::from(1_u64), &(*MODULUS) - BigUint::from(1_u64)] {
+            fe_to_mont(&mut a_mont, &big_to_6u64(a_big));
+            assert!(!bool::from(fe_inv(&mut actual_mont, &a_mont)));
+            fe_to_norm(&mut actual_norm, &actual_mont);
+            assert_eq!(big_to_6u64(a_big), actual_norm);
+        }
+    }
+
+    #[test]
+    fn test_fe_sqrt() {
+        let mut actual_mont = W6x64::default();
+        let mut actual_norm = [0_u64; 6];
+        let mut a_mont = W6x64::default();
+        let exponent = (&(*MODULUS) - BigUint::from(1_u64)) >> 1;
+
+        for _i in 0..100_000 {
+            let a_big = rnd_big_mod_n();
+            fe_to_mont(&mut a_mont, &big_to_6u64(&a_big));
+
+            let is_square = a_big.modpow(&exponent, &MODULUS) != &(*MODULUS) - BigUint::from(1_u64);
+            assert_eq!(is_square, bool::from(fe_sqrt(&mut actual_mont, &a_mont)));
+            assert_eq!(is_square, bool::from(fe_is_square(&a_mont)));
+
+            // Either root squares back to a, otherwise to -a
+            fe_to_norm(&mut actual_norm, &actual_mont);
+            let root_big = six_u64_to_big(&actual_norm);
+            let expected =
+                if is_square { a_big.clone() } else { (&(*MODULUS) - &a_big) % &(*MODULUS) };
+            assert_eq!(expected, (&root_big * &root_big) % &(*MODULUS));
+        }
+
+        let zero = W6x64::default();
+        assert!(bool::from(fe_sqrt(&mut actual_mont, &zero)));
+        assert_eq!(zero, actual_mont);
+    }
+
+    #[test]
+    fn test_fe_jacobi_vartime() {
+        let mut a_mont = W6x64::default();
+        let exponent = (&(*MODULUS) - BigUint::from(1_u64)) >> 1;
+
+        for _i in 0..100_000 {
+            let a_big = rnd_big_mod_n();
+            fe_to_mont(&mut a_mont, &big_to_6u64(&a_big));
+
+            let legendre = a_big.modpow(&exponent, &MODULUS);
+            let expected = if legendre == BigUint::from(1_u64) {
+                1
+            } else if legendre == BigUint::default() {
+                0
+            } else {
+                -1
+            };
+            assert_eq!(expected, fe_jacobi_vartime(&a_mont));
+            assert_eq!(expected != -1, bool::from(fe_is_square(&a_mont)));
+        }
+
+        assert_eq!(0, fe_jacobi_vartime(&W6x64::default()));
+    }
+
+    #[test]
+    fn test_fe_pow() {
+        let mut actual_mont = W6x64::default();
+        let mut actual_norm = [0_u64; 6];
+        let mut vartime_mont = W6x64::default();
+        let mut a_mont = W6x64::default();
+
+        for _i in 0..100_000 {
+            let a_big = rnd_big_mod_n();
+            let mut exponent = [0_u64; 6];
+            rand::thread_rng().fill(&mut exponent[..]);
+            exponent[5] >>= rand::thread_rng().gen_range(0..64); // Vary the exponent length
+            fe_to_mont(&mut a_mont, &big_to_6u64(&a_big));
+
+            let expected = a_big.modpow(&six_u64_to_big(&exponent), &MODULUS);
+            fe_pow(&mut actual_mont, &a_mont, &exponent);
+            fe_pow_vartime(&mut vartime_mont, &a_mont, &exponent);
+
+            fe_to_norm(&mut actual_norm, &actual_mont);
+            assert_eq!(big_to_6u64(&expected), actual_norm);
+            assert_eq!(actual_mont, vartime_mont);
+        }
+
+        // Zero exponent (in any length) gives one, and Fermat's little theorem gives the inverse
+        let a_big = rnd_big_mod_n();
+        fe_to_mont(&mut a_mont, &big_to_6u64(&a_big));
+        for exponent in &[&[][..], &[0_u64][..], &[0_u64; 6][..]] {
+            fe_pow(&mut actual_mont, &a_mont, exponent);
+            fe_pow_vartime(&mut vartime_mont, &a_mont, exponent);
+            fe_to_norm(&mut actual_norm, &actual_mont);
+            assert_eq!([1, 0, 0, 0, 0, 0], actual_norm);
+            assert_eq!(actual_mont, vartime_mont);
+        }
+        let mut inverse_mont = W6x64::default();
+        let _ = fe_inv(&mut inverse_mont, &a_mont);
+        fe_pow(&mut actual_mont, &a_mont, &big_to_6u64(&(&(*MODULUS) - BigUint::from(2_u64))));
+        assert_eq!(inverse_mont, actual_mont);
+    }
+
+    #[test]
+    fn test_ct_eq_select_swap() {
+        let mut a_mont = W6x64::default();
+        let mut b_mont = W6x64::default();
+
+        for _i in 0..1_000_000 {
+            fe_to_mont(&mut a_mont, &big_to_6u64(&rnd_big_mod_n()));
+            fe_to_mont(&mut b_mont, &big_to_6u64(&rnd_big_mod_n()));
+            let bit = rand::thread_rng().gen_range(0..2_u64);
+            let choice = Choice::from_bit(bit);
+
+            assert_eq!(a_mont == b_mont, bool::from(a_mont.ct_eq(&b_mont)));
+            assert!(bool::from(a_mont.ct_eq(&a_mont)));
+            assert_eq!(a_mont == W6x64::default(), bool::from(a_mont.ct_is_zero()));
+
+            let expected = if bit == 1 { b_mont } else { a_mont };
+            assert_eq!(expected, W6x64::conditional_select(&a_mont, &b_mont, choice));
+
+            let (mut a_swap, mut b_swap) = (a_mont, b_mont);
+            W6x64::conditional_swap(&mut a_swap, &mut b_swap, choice);
+            assert_eq!(
+                if bit == 1 { (b_mont, a_mont) } else { (a_mont, b_mont) },
+                (a_swap, b_swap)
+            );
+        }
+
+        // Values differing in a single bit of any limb must compare unequal
+        for limb in 0..6 {
+            let mut c_mont = a_mont;
+            c_mont.v[limb] ^= 1 << 63;
+            assert!(!bool::from(a_mont.ct_eq(&c_mont)));
+            assert!(!bool::from(c_mont.ct_is_zero()));
+        }
+        assert!(bool::from(W6x64::default().ct_is_zero()));
+
+        let (t, f) = (Choice::from_bit(1), Choice::from_bit(0));
+        assert_eq!((u64::MAX, 0), (t.mask(), f.mask()));
+        assert!(bool::from(t & t) && !bool::from(t & f) && bool::from(t | f) && bool::from(!f));
+    }
+
+    // Disassemble this test binary and confirm that no conditional jump follows the final
+    // subtraction of the modulus in either Rust backend; Needs objdump, so run it with --ignored
+    #[test]
+    #[ignore = "needs objdump"]
+    #[cfg(target_arch = "x86_64")]
+    fn test_fe_mont_mul_final_reduction_branch_free() {
+        type MulFn = fn(&mut W6x64, &W6x64, &W6x64);
+        #[allow(unused_mut)]
+        let mut backends: Vec<(&str, MulFn)> =
+            vec![("mont2::arith::fe_mont_mul_raw", fe_mont_mul_raw)];
+        #[cfg(all(feature = "intrinsics", target_arch = "x86_64"))]
+        backends.push(("mont2::arith::fe_mont_mul_intrinsics", fe_mont_mul_intrinsics));
+
+        // Calling through opaque function pointers keeps standalone copies in the binary
+        let mut result = W6x64::default();
+        for backend in backends.iter().map(|(_, f)| unsafe { std::ptr::read_volatile(f) }) {
+            backend(&mut result, &W6x64::default(), &W6x64::default());
+        }
+
+        let exe = std::env::current_exe().unwrap();
+        let output = std::process::Command::new("objdump")
+            .args(["-d", "-C", "--no-show-raw-insn"])
+            .arg(&exe)
+            .output()
+            .expect("objdump is required for the disassembly check");
+        assert!(output.status.success(), "objdump failed on {}", exe.display());
+        let listing = String::from_utf8_lossy(&output.stdout);
+
+        for (name, _) in &backends {
+            let header = format!(" <{}>:", name);
+            let body: Vec<&str> = listing
+                .lines()
+                .skip_while(|line| !line.ends_with(&header))
+                .skip(1)
+                .take_while(|line| !line.trim().is_empty())
+                .filter_map(|line| line.split('\t').nth(1))
+                .collect();
+            assert!(!body.is_empty(), "{} not found in disassembly", name);
+
+            let last_sbb = body.iter().rposition(|insn| insn.starts_with("sbb")).unwrap();
+            let branches: Vec<&&str> = body[last_sbb..]
+                .iter()
+                .filter(|insn| insn.starts_with('j') && !insn.starts_with("jmp"))
+                .collect();
+            assert!(
+                branches.is_empty(),
+                "{} branches after final subtraction: {:?}",
+                name,
+                branches
+            );
+        }
+    }
+
+    #[test]
+    fn test_fe_lazy() {
+        let mut actual = W6x64::default();
+        let mut reduced = W6x64::default();
+        let mut exp_mont = BigUint::default();
+        let n_twice = &(*MODULUS) * 2_u64;
+        let n_four = &(*MODULUS) * 4_u64;
+
+        // Random values in [0, 2N) and [0, 4N), plus both ends of each range
+        let one = BigUint::from(1_u64);
+        let mut samples: Vec<(BigUint, BigUint)> = (0..100_000)
+            .map(|_| {
+                let mut rnd_bytes = [0_u8; 64];
+                rand::thread_rng().fill(&mut rnd_bytes[..]);
+                let x = BigUint::from_bytes_le(&rnd_bytes);
+                (&x % &n_twice, &x % &n_four)
+            })
+            .collect();
+        for a in &[BigUint::default(), one.clone(), &(*MODULUS) - &one, MODULUS.clone()] {
+            for b in &[&n_twice - &one, &n_four - &one] {
+                samples.push((a.clone(), b.clone()));
+                samples.push(((&n_twice - &one), b.clone()));
+            }
+        }
+
+        for (a_big, b_big) in &samples {
+            let b2_big = b_big % &n_twice;
+            let a = W6x64 { v: big_to_6u64(a_big) };
+            let b = W6x64 { v: big_to_6u64(b_big) };
+            let b2 = W6x64 { v: big_to_6u64(&b2_big) };
+
+            // [0, 2N) + [0, 2N) -> exactly the sum in [0, 4N)
+            fe_add_lazy(&mut actual, &a, &b2);
+            assert_eq!(big_to_6u64(&(a_big + &b2_big)), actual.v);
+            fe_reduce(&mut reduced, &actual);
+            assert_eq!(big_to_6u64(&((a_big + &b2_big) % &(*MODULUS))), reduced.v);
+
+            // [0, 2N) - [0, 2N) -> exactly a + 2N - b in (0, 4N)
+            fe_sub_lazy(&mut actual, &a, &b2);
+            assert_eq!(big_to_6u64(&(a_big + &n_twice - &b2_big)), actual.v);
+
+            // [0, 4N) * [0, 2N) -> [0, 2N), congruent to the fully reduced product
+            mont_mul_biguint(&mut exp_mont, b_big, a_big);
+            fe_mont_mul_lazy(&mut actual, &b, &a);
+            assert!(six_u64_to_big(&actual.v) < n_twice);
+            fe_reduce(&mut reduced, &actual);
+            assert_eq!(big_to_6u64(&exp_mont), reduced.v);
+
+            // The regular multiplication fully reduces the same inputs
+            fe_mont_mul_portable(&mut actual, &b, &a);
+            assert_eq!(big_to_6u64(&exp_mont), actual.v);
+
+            // Anything in [0, 4N) reduces to [0, N)
+            fe_reduce(&mut reduced, &b);
+            assert_eq!(big_to_6u64(&(b_big % &(*MODULUS))), reduced.v);
+        }
+    }
+
+    #[test]
+    fn test_fe_batch_inv() {
+        use crate::arith::fe_batch_inv;
+        let mut expected = W6x64::default();
+
+        for len in 0..200 {
+            // Random values with roughly one in eight replaced by zero
+            let original: Vec<W6x64> = (0..len)
+                .map(|_| {
+                    let mut a_mont = W6x64::default();
+                    if rand::thread_rng().gen_range(0..8) != 0 {
+                        fe_to_mont(&mut a_mont, &big_to_6u64(&rnd_big_mod_n()));
+                    }
+                    a_mont
+                })
+                .collect();
+
+            let mut actual = original.clone();
+            let mut scratch = vec![W6x64::default(); len];
+            fe_batch_inv(&mut actual, &mut scratch);
+            for (a_mont, actual_inv) in original.iter().zip(actual.iter()) {
+                let _ = fe_inv(&mut expected, a_mont);
+                assert_eq!(&expected, actual_inv);
+            }
+        }
+
+        let mut zeros = [W6x64::default(); 3];
+        fe_batch_inv(&mut zeros, &mut [W6x64::default(); 3]);
+        assert_eq!([W6x64::default(); 3], zeros);
+    }
+
+    #[test]
+    #[allow(clippy::similar_names)]
+    fn test_fe_bytes() {
+        let mut a_mont = W6x64::default();
+
+        for _i in 0..100_000 {
+            let a_big = rnd_big_mod_n();
+            fe_to_mont(&mut a_mont, &big_to_6u64(&a_big));
+
+            let mut expected_le = a_big.to_bytes_le();
+            expected_le.resize(FE_BYTES, 0);
+            let mut expected_be = expected_le.clone();
+            expected_be.reverse();
+
+            let actual_le = a_mont.to_bytes_le();
+            let actual_be = a_mont.to_bytes_be();
+            assert_eq!(expected_le, actual_le.to_vec());
+            assert_eq!(expected_be, actual_be.to_vec());
+            assert_eq!(Ok(a_mont), W6x64::from_bytes_le(&actual_le));
+            assert_eq!(Ok(a_mont), W6x64::from_bytes_be(&actual_be));
+        }
+
+        // Values at and above N are refused, N-1 and zero are accepted
+        for (value, accept) in &[
+            (&(*MODULUS) - BigUint::from(1_u64), true),
+            (BigUint::default(), true),
+            (MODULUS.clone(), false),
+            (&(*MODULUS) + BigUint::from(1_u64), false),
+            (&(*R) - BigUint::from(1_u64), false),
+        ] {
+            let le_bytes = value.to_bytes_le();
+            let mut bytes = [0_u8; FE_BYTES];
+            bytes[..le_bytes.len()].copy_from_slice(&le_bytes);
+            let from_le = W6x64::from_bytes_le(&bytes);
+            bytes.reverse();
+            let from_be = W6x64::from_bytes_be(&bytes);
+            assert_eq!(from_le, from_be);
+            if *accept {
+                assert_eq!(big_to_6u64(value), {
+                    let mut norm = [0_u64; 6];
+                    fe_to_norm(&mut norm, &from_le.unwrap());
+                    norm
+                });
+            } else {
+                assert_eq!(Err(FieldError::NotCanonical), from_le);
+            }
+        }
+    }
+
+    // Sum and Product, by reference and by value, of the integers 1..=20 embedded via from_u64 come
+    // to 210 and 20!, while over nothing they come to zero and one
+    fn check_sum_product<T, F>(from_u64: F)
+    where
+        T: Copy + PartialEq + std::fmt::Debug + Sum + Product,
+        T: for<'a> Sum<&'a T> + for<'a> Product<&'a T>,
+        F: Fn(u64) -> T,
+    {
+        let items: Vec<T> = (1..=20).map(&from_u64).collect();
+        assert_eq!(from_u64(210), items.iter().sum());
+        assert_eq!(from_u64(2_432_902_008_176_640_000), items.iter().product());
+        assert_eq!(from_u64(210), items.iter().copied().sum());
+        assert_eq!(from_u64(2_432_902_008_176_640_000), items.into_iter().product());
+        assert_eq!(from_u64(0), std::iter::empty::<T>().sum());
+        assert_eq!(from_u64(1), std::iter::empty::<T>().product());
+    }
+
+    #[test]
+    fn test_fp_ops() {
+        let minus_one = &(*MODULUS) - BigUint::from(1_u64);
+
+        for _i in 0..100_000 {
+            let a_big = rnd_big_mod_n();
+            let b_big = rnd_big_mod_n();
+            let a = Fp::from_norm(&big_to_6u64(&a_big)).unwrap();
+            let b = Fp::from_norm(&big_to_6u64(&b_big)).unwrap();
+
+            let sum = (&a_big + &b_big) % &(*MODULUS);
+            let diff = (&a_big + &(*MODULUS) - &b_big) % &(*MODULUS);
+            let prod = (&a_big * &b_big) % &(*MODULUS);
+            let neg = (&(*MODULUS) - &a_big) % &(*MODULUS);
+            assert_eq!(big_to_6u64(&sum), (a + b).to_norm());
+            assert_eq!(big_to_6u64(&diff), (a - b).to_norm());
+            assert_eq!(big_to_6u64(&prod), (a * b).to_norm());
+            assert_eq!(big_to_6u64(&neg), (-a).to_norm());
+            assert_eq!(big_to_6u64(&((&a_big * &a_big) % &(*MODULUS))), a.square().to_norm());
+
+            // Reference and assignment forms agree with the by-value operators
+            let (a_ref, b_ref) = (&a, &b);
+            assert_eq!(a + b, a_ref + b_ref);
+            assert_eq!(a * b, a * b_ref);
+            assert_eq!(a - b, a_ref - b);
+            assert_eq!(-a, -a_ref);
+            let mut c = a;
+            c += b;
+            c *= &b;
+            c -= a;
+            assert_eq!((a + b) * b - a, c);
+
+            if a_big != BigUint::default() {
+                assert_eq!(Fp::ONE, a * a.invert().0);
+            }
+        }
+
+        check_sum_product(Fp::from_u64);
+        assert_eq!([1, 0, 0, 0, 0, 0], Fp::ONE.to_norm());
+        assert_eq!(Fp::ZERO, -Fp::ZERO);
+        assert_eq!(big_to_6u64(&minus_one), (-Fp::ONE).to_norm());
+        assert!(bool::from(Fp::ZERO.invert().1));
+        assert!(Fp::from_norm(&big_to_6u64(&MODULUS)).is_err());
+    }
+
+    #[test]
+    fn test_dispatch_backends() {
+        assert!(Backend::Portable.is_supported());
+        #[cfg(all(feature = "asm", target_arch = "x86_64"))]
+        assert_eq!(
+            is_x86_feature_detected!("bmi2") && is_x86_feature_detected!("adx"),
+            Backend::Asm.is_supported()
+        );
+        assert!(dispatch::detect_backend().is_supported());
+        let mut exercised = 0;
+        for backend in [Backend::Portable, Backend::Intrinsics, Backend::Asm] {
+            if !dispatch::force_backend(Some(backend)) {
+                assert!(!backend.is_supported());
+                continue;
+            }
+            exercised += 1;
+            assert_eq!(backend, dispatch::backend());
+            for _i in 0..100_000 {
+                let a_big = rnd_big_mod_n();
+                let b_big = rnd_big_mod_n();
+                let a = W6x64 { v: big_to_6u64(&a_big) };
+                let b = W6x64 { v: big_to_6u64(&b_big) };
+                let mut expected = BigUint::default();
+                let mut result = W6x64::default();
+                mont_mul_biguint(&mut expected, &a_big, &b_big);
+                crate::fe_mont_mul(&mut result, &a, &b);
+                assert_eq!(big_to_6u64(&expected), result.v);
+                mont_mul_biguint(&mut expected, &a_big, &a_big);
+                crate::fe_mont_sqr(&mut result, &a);
+                assert_eq!(big_to_6u64(&expected), result.v);
+            }
+        }
+        assert!(exercised > 0);
+        assert!(dispatch::force_backend(None));
+        assert_eq!(dispatch::detect_backend(), dispatch::backend());
+    }
+
+    fn big_to_limbs<const LIMBS: usize>(x: &BigUint) -> [u64; LIMBS] {
+        let mut result = [0_u64; LIMBS];
+        for (limb, digit) in result.iter_mut().zip(x.to_u64_digits()) {
+            *limb = digit;
+        }
+        result
+    }
+
+    // Derive the Montgomery constants the slow way, as a reference for MontField::new
+    fn mont_field_from_big<const LIMBS: usize>(n: &BigUint) -> MontField<LIMBS> {
+        let r = BigUint::from(1_u64) << (64 * LIMBS);
+        let word = BigUint::from(1_u64) << 64;
+        let n_inv = n.modpow(&(BigUint::from(u64::MAX) >> 1), &word); // phi(2^64) - 1
+        MontField {
+            n: big_to_limbs(n),
+            n_prime: big_to_limbs::<1>(&(&word - n_inv))[0],
+            r_mod_n: big_to_limbs(&(&r % n)),
+            r_squared: big_to_limbs(&(&r * &r % n)),
+            correction: big_to_limbs(&(&r - n)),
+            asm_mul: None,
+        }
+    }
+
+    // Compare every MontField operation against BigUint for random inputs, plus the extremes
+    fn check_mont_field<const LIMBS: usize>(field: &MontField<LIMBS>, n: &BigUint) {
+        let r = BigUint::from(1_u64) << (64 * LIMBS);
+        let r_inv = r.modpow(&(n - BigUint::from(2_u64)), n);
+        let n_minus_one = n - BigUint::from(1_u64);
+        for i in 0..20_000 {
+            let (a_big, b_big) = match i {
+                0 => (BigUint::default(), BigUint::default()),
+                1 => (n_minus_one.clone(), n_minus_one.clone()),
+                2 => (BigUint::default(), n_minus_one.clone()),
+                _ => {
+                    let mut rnd_bytes = [0_u8; 160];
+                    rand::thread_rng().fill(&mut rnd_bytes[..]);
+                    let a_big = BigUint::from_bytes_le(&rnd_bytes[..80]) % n;
+                    (a_big, BigUint::from_bytes_le(&rnd_bytes[80..]) % n)
+                }
+            };
+            let a = big_to_limbs::<LIMBS>(&a_big);
+            let b = big_to_limbs::<LIMBS>(&b_big);
+            let mut actual = [0_u64; LIMBS];
+
+            field.add(&mut actual, &a, &b);
+            assert_eq!(big_to_limbs::<LIMBS>(&((&a_big + &b_big) % n)), actual);
+            field.sub(&mut actual, &a, &b);
+            assert_eq!(big_to_limbs::<LIMBS>(&((&a_big + n - &b_big) % n)), actual);
+            let product = big_to_limbs::<LIMBS>(&(&a_big * &b_big * &r_inv % n));
+            field.mont_mul(&mut actual, &a, &b);
+            assert_eq!(product, actual);
+            field.mont_mul_portable(&mut actual, &a, &b);
+            assert_eq!(product, actual);
+            #[cfg(all(feature = "asm", target_arch = "x86_64"))]
+            if field.asm_mul.is_some() && Backend::Asm.is_supported() {
+                field.mont_mul_asm(&mut actual, &a, &b);
+                assert_eq!(product, actual);
+            }
+
+            let mut a_mont = [0_u64; LIMBS];
+            field.to_mont(&mut a_mont, &a);
+            assert_eq!(big_to_limbs::<LIMBS>(&(&a_big * &r % n)), a_mont);
+            field.to_norm(&mut actual, &a_mont);
+            assert_eq!(a, actual);
+
+            // Inversion costs a whole exponentiation, so only sample it
+            if i % 16 == 0 {
+                let is_zero = field.inv(&mut actual, &a);
+                assert_eq!(a_big == BigUint::default(), bool::from(is_zero));
+                if !bool::from(is_zero) {
+                    let a_inv = a_big.modpow(&(n - BigUint::from(2_u64)), n);
+                    assert_eq!(big_to_limbs::<LIMBS>(&(a_inv * &r * &r % n)), actual);
+                }
+            }
+
+            // Any double-width value reduces, even with halves at or above N
+            let (mut lo, mut hi) = ([0_u64; LIMBS], [u64::MAX; LIMBS]);
+            if i > 0 {
+                rand::thread_rng().fill(&mut lo[..]);
+                rand::thread_rng().fill(&mut hi[..]);
+            }
+            let wide_big = limbs_to_big(&lo) + (limbs_to_big(&hi) << (64 * LIMBS));
+            field.reduce_wide(&mut actual, &lo, &hi);
+            assert_eq!(big_to_limbs::<LIMBS>(&(wide_big * &r % n)), actual);
+        }
+    }
+
+    fn limbs_to_big(limbs: &[u64]) -> BigUint {
+        let bytes: Vec<u8> = limbs.iter().flat_map(|limb| limb.to_le_bytes().to_vec()).collect();
+        BigUint::from_bytes_le(&bytes)
+    }
+
+    #[test]
+    fn test_correction() {
+        let r = BigUint::from(1_u64) << 384;
+        assert_eq!(big_to_limbs::<6>(&(r - &*MODULUS)), correction(N));
+        assert_eq!(correction(N), BLS12_381_FP.correction);
+    }
+
+    #[test]
+    fn test_mont_field() {
+        assert_eq!(mont_field_from_big::<6>(&MODULUS), BLS12_381_FP);
+        check_mont_field(&BLS12_381_FP, &MODULUS);
+
+        // The generic code agrees with the hand-tuned BLS12-381 functions
+        for _i in 0..10_000 {
+            let a = W6x64 { v: big_to_6u64(&rnd_big_mod_n()) };
+            let b = W6x64 { v: big_to_6u64(&rnd_big_mod_n()) };
+            let (mut expected, mut actual) = (W6x64::default(), [0_u64; 6]);
+            fe_add(&mut expected, &a, &b);
+            BLS12_381_FP.add(&mut actual, &a.v, &b.v);
+            assert_eq!(expected.v, actual);
+            fe_sub(&mut expected, &a, &b);
+            BLS12_381_FP.sub(&mut actual, &a.v, &b.v);
+            assert_eq!(expected.v, actual);
+            fe_mont_mul_portable(&mut expected, &a, &b);
+            BLS12_381_FP.mont_mul(&mut actual, &a.v, &b.v);
+            assert_eq!(expected.v, actual);
+        }
+
+        // BLS12-381 scalar field r, 255 bits in four limbs
+        let bls12_381_r = BigUint::from_str_radix(
+            "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001",
+            16,
+        )
+        .unwrap();
+        let field = MontField::new(big_to_limbs::<4>(&bls12_381_r));
+        assert_eq!(mont_field_from_big(&bls12_381_r), field);
+        check_mont_field(&field, &bls12_381_r);
+
+        // secp256k1 base field p, which uses every bit of its four limbs
+        let secp256k1_p = BigUint::from_str_radix(
+            "fffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f",
+            16,
+        )
+        .unwrap();
+        let field = MontField::new(big_to_limbs::<4>(&secp256k1_p));
+        assert_eq!(mont_field_from_big(&secp256k1_p), field);
+        check_mont_field(&field, &secp256k1_p);
+
+        // Largest prime below 2^64, in a single limb
+        let p64 = BigUint::from(u64::MAX - 58);
+        let field = MontField::new(big_to_limbs::<1>(&p64));
+        assert_eq!(mont_field_from_big(&p64), field);
+        check_mont_field(&field, &p64);
+    }
+
+    #[test]
+    fn test_fr_ops() {
+        let order = BigUint::from_str_radix(
+            "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001",
+            16,
+        )
+        .unwrap();
+        let rnd_big_mod_r = || {
+            let mut rnd_bytes = [0_u8; 64];
+            rand::thread_rng().fill(&mut rnd_bytes[..]);
+            BigUint::from_bytes_le(&rnd_bytes) % &order
+        };
+
+        for _i in 0..100_000 {
+            let a_big = rnd_big_mod_r();
+            let b_big = rnd_big_mod_r();
+            let a = Fr::from_norm(&big_to_limbs(&a_big)).unwrap();
+            let b = Fr::from_norm(&big_to_limbs(&b_big)).unwrap();
+            assert_eq!(big_to_limbs::<4>(&a_big), a.to_norm());
+
+            let expected = |x: BigUint| big_to_limbs::<4>(&(x % &order));
+            assert_eq!(expected(&a_big + &b_big), (a + b).to_norm());
+            assert_eq!(expected(&a_big + &order - &b_big), (a - b).to_norm());
+            assert_eq!(expected(&a_big * &b_big), (a * b).to_norm());
+            assert_eq!(expected(&a_big * &a_big), a.square().to_norm());
+            assert_eq!(expected(&order - &a_big), (-a).to_norm());
+
+            let mut c = a;
+            c *= b;
+            c -= a;
+            c += &b;
+            assert_eq!(a * b - a + b, c);
+
+            if a_big != BigUint::default() {
+                assert_eq!(Fr::ONE, a * a.invert().0);
+                assert!(!bool::from(a.invert().1));
+            }
+
+            let mut wide = [0_u64; 8];
+            rand::thread_rng().fill(&mut wide[..]);
+            let wide_bytes: Vec<u8> = wide.iter().flat_map(|x| x.to_le_bytes().to_vec()).collect();
+            let wide_big = BigUint::from_bytes_le(&wide_bytes);
+            assert_eq!(expected(wide_big), Fr::from_wide(&wide).to_norm());
+
+            let bytes = a.to_bytes_le();
+            let mut bytes_be = a.to_bytes_be();
+            assert_eq!(a, Fr::from_bytes_le(&bytes).unwrap());
+            assert_eq!(a, Fr::from_bytes_be(&bytes_be).unwrap());
+            bytes_be.reverse();
+            assert_eq!(bytes, bytes_be);
+        }
+
+        check_sum_product(Fr::from_u64);
+        assert_eq!([1, 0, 0, 0], Fr::ONE.to_norm());
+        assert_eq!(Fr::ZERO, -Fr::ZERO);
+        assert!(bool::from(Fr::ZERO.invert().1));
+        assert_eq!(Fr::ZERO, Fr::from_wide(&[0; 8]));
+        assert_eq!(Fr::ONE, Fr::from_wide(&[1, 0, 0, 0, 0, 0, 0, 0]));
+        assert_eq!(
+            big_to_limbs::<4>(&(((BigUint::from(1_u64) << 512) - 1_u64) % &order)),
+            Fr::from_wide(&[u64::MAX; 8]).to_norm()
+        );
+        assert!(Fr::from_norm(&big_to_limbs(&order)).is_err());
+        assert!(Fr::from_bytes_le(&[0xff; FR_BYTES]).is_err());
+    }
+
+    #[test]
+    fn test_bn254_fields() {
+        let q = BigUint::from_str_radix(
+            "30644e72e131a029b85045b68181585d97816a916871ca8d3c208c16d87cfd47",
+            16,
+        )
+        .unwrap();
+        assert_eq!(mont_field_from_big(&q), BN254_FQ);
+        check_mont_field(&BN254_FQ, &q);
+
+        let r = BigUint::from_str_radix(
+            "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001",
+            16,
+        )
+        .unwrap();
+        assert_eq!(mont_field_from_big(&r), BN254_FR);
+        check_mont_field(&BN254_FR, &r);
+    }
+
+    #[test]
+    fn test_bls12_377_fields() {
+        let q = BigUint::from_str_radix(
+            "1ae3a4617c510eac63b05c06ca1493b1a22d9f300f5138f1ef3622fba094800170b5d44300000008508c00000000001",
+            16,
+        )
+        .unwrap();
+        assert_eq!(mont_field_from_big(&q), BLS12_377_FQ);
+        check_mont_field(&BLS12_377_FQ, &q);
+
+        let r = BigUint::from_str_radix(
+            "12ab655e9a2ca55660b44d1e5c37b00159aa76fed00000010a11800000000001",
+            16,
+        )
+        .unwrap();
+        assert_eq!(mont_field_from_big(&r), BLS12_377_FR);
+        check_mont_field(&BLS12_377_FR, &r);
+    }
+
+    // Fp2 elements as (c0, c1) pairs of BigUint, each reduced modulo N, for use as a reference
+    type BigFp2 = (BigUint, BigUint);
+
+    fn rnd_big_fp2() -> BigFp2 {
+        (rnd_big_mod_n(), rnd_big_mod_n())
+    }
+
+    fn fp2_from_big(x: &BigFp2) -> Fp2 {
+        let c0 = Fp::from_norm(&big_to_6u64(&x.0)).unwrap();
+        Fp2::new(c0, Fp::from_norm(&big_to_6u64(&x.1)).unwrap())
+    }
+
+    fn fp2_to_big(x: &Fp2) -> BigFp2 {
+        (limbs_to_big(&x.c0.to_norm()), limbs_to_big(&x.c1.to_norm()))
+    }
+
+    // Schoolbook (a0 + a1*u)(b0 + b1*u) with u^2 = -1
+    fn fp2_mul_big(a: &BigFp2, b: &BigFp2) -> BigFp2 {
+        let n = &(*MODULUS);
+        let c0 = (&a.0 * &b.0 + n * n - &a.1 * &b.1) % n;
+        (c0, (&a.0 * &b.1 + &a.1 * &b.0) % n)
+    }
+
+    fn fp2_pow_big(a: &BigFp2, exponent: &BigUint) -> BigFp2 {
+        let mut result = (BigUint::from(1_u64), BigUint::default());
+        for i in (0..exponent.bits()).rev() {
+            result = fp2_mul_big(&result, &result);
+            if exponent.bit(i) {
+                result = fp2_mul_big(&result, a);
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_fp2_ops() {
+        let n = &(*MODULUS);
+        for _i in 0..10_000 {
+            let (a_big, b_big) = (rnd_big_fp2(), rnd_big_fp2());
+            let (a, b) = (fp2_from_big(&a_big), fp2_from_big(&b_big));
+            assert_eq!(a_big, fp2_to_big(&a));
+
+            let sum = ((&a_big.0 + &b_big.0) % n, (&a_big.1 + &b_big.1) % n);
+            let diff = ((&a_big.0 + n - &b_big.0) % n, (&a_big.1 + n - &b_big.1) % n);
+            assert_eq!(sum, fp2_to_big(&(a + b)));
+            assert_eq!(diff, fp2_to_big(&(a - b)));
+            assert_eq!((n - &a_big.0) % n, limbs_to_big(&(-a).c0.to_norm()));
+            assert_eq!((n - &a_big.1) % n, limbs_to_big(&(-a).c1.to_norm()));
+            assert_eq!(fp2_mul_big(&a_big, &b_big), fp2_to_big(&(a * b)));
+            assert_eq!(fp2_mul_big(&a_big, &a_big), fp2_to_big(&a.square()));
+            assert_eq!((a_big.0.clone(), (n - &a_big.1) % n), fp2_to_big(&a.conjugate()));
+            let nonresidue = (BigUint::from(1_u64), BigUint::from(1_u64));
+            assert_eq!(fp2_mul_big(&a_big, &nonresidue), fp2_to_big(&a.mul_by_nonresidue()));
+            let norm = (&a_big.0 * &a_big.0 + &a_big.1 * &a_big.1) % n;
+            assert_eq!(norm, limbs_to_big(&a.norm().to_norm()));
+
+            let (a_inv, is_zero) = a.invert();
+            assert!(!bool::from(is_zero));
+            assert_eq!(Fp2::ONE, a * a_inv);
+            assert_eq!(Fp2::ONE, a_inv * a);
+
+            assert_eq!(a.conjugate(), a.frobenius_map(1));
+            assert_eq!(a, a.frobenius_map(2));
+            assert!(bool::from(a.ct_eq(&a)));
+            assert!(!bool::from(a.ct_eq(&b)));
+            assert_eq!(b, Fp2::conditional_select(&a, &b, Choice::from_bit(1)));
+            assert_eq!(a, Fp2::conditional_select(&a, &b, Choice::from_bit(0)));
+
+            let mut c = a;
+            c *= &b;
+            c -= a;
+            c += b;
+            assert_eq!(a * b - a + b, c);
+        }
+
+        // The Frobenius map really is exponentiation by N, and u^2 = -1 is not a square in Fp
+        let a_big = rnd_big_fp2();
+        let a = fp2_from_big(&a_big);
+        assert_eq!(fp2_pow_big(&a_big, n), fp2_to_big(&a.frobenius_map(1)));
+        let u = Fp2::new(Fp::ZERO, Fp::ONE);
+        assert_eq!(-Fp2::ONE, u.square());
+        assert_eq!(Fp2::new(Fp::ONE, Fp::ONE), Fp2::ONE.mul_by_nonresidue());
+        assert!(!bool::from(fe_is_square((-Fp::ONE).as_mont())));
+
+        check_sum_product(|x| Fp2::new(Fp::from_u64(x), Fp::ZERO));
+        assert!(bool::from(Fp2::ZERO.invert().1));
+        assert_eq!(Fp2::ZERO, Fp2::ZERO.invert().0);
+        assert!(bool::from(Fp2::ZERO.ct_is_zero()));
+        assert_eq!(Fp2::ZERO, -Fp2::ZERO);
+    }
+
+    // Fp6 elements as coefficients of 1, v, v^2, each a BigFp2, for use as a naive reference
+    type BigFp6 = [BigFp2; 3];
+
+    fn rnd_big_fp6() -> BigFp6 {
+        [rnd_big_fp2(), rnd_big_fp2(), rnd_big_fp2()]
+    }
+
+    fn fp6_from_big(x: &BigFp6) -> Fp6 {
+        Fp6::new(fp2_from_big(&x[0]), fp2_from_big(&x[1]), fp2_from_big(&x[2]))
+    }
+
+    fn fp6_to_big(x: &Fp6) -> BigFp6 {
+        [fp2_to_big(&x.c0), fp2_to_big(&x.c1), fp2_to_big(&x.c2)]
+    }
+
+    fn fp2_add_big(a: &BigFp2, b: &BigFp2) -> BigFp2 {
+        let n = &(*MODULUS);
+        ((&a.0 + &b.0) % n, (&a.1 + &b.1) % n)
+    }
+
+    // Schoolbook polynomial product, then v^3 = 1 + u folds the two top coefficients back down
+    fn fp6_mul_big(a: &BigFp6, b: &BigFp6) -> BigFp6 {
+        let zero = (BigUint::default(), BigUint::default());
+        let mut d = vec![zero; 5];
+        for i in 0..3 {
+            for j in 0..3 {
+                d[i + j] = fp2_add_big(&d[i + j], &fp2_mul_big(&a[i], &b[j]));
+            }
+        }
+        let xi = (BigUint::from(1_u64), BigUint::from(1_u64));
+        [
+            fp2_add_big(&d[0], &fp2_mul_big(&d[3], &xi)),
+            fp2_add_big(&d[1], &fp2_mul_big(&d[4], &xi)),
+            d[2].clone(),
+        ]
+    }
+
+    fn fp6_pow_big(a: &BigFp6, exponent: &BigUint) -> BigFp6 {
+        let zero = (BigUint::default(), BigUint::default());
+        let mut result = [(BigUint::from(1_u64), BigUint::default()), zero.clone(), zero];
+        for i in (0..exponent.bits()).rev() {
+            result = fp6_mul_big(&result, &result);
+            if exponent.bit(i) {
+                result = fp6_mul_big(&result, a);
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_fp6_ops() {
+        let n = &(*MODULUS);
+        let zero = (BigUint::default(), BigUint::default());
+        for _i in 0..5_000 {
+            let (a_big, b_big) = (rnd_big_fp6(), rnd_big_fp6());
+            let (a, b) = (fp6_from_big(&a_big), fp6_from_big(&b_big));
+            assert_eq!(a_big, fp6_to_big(&a));
+
+            let sum: Vec<BigFp2> = (0..3).map(|i| fp2_add_big(&a_big[i], &b_big[i])).collect();
+            assert_eq!(sum, fp6_to_big(&(a + b)).to_vec());
+            assert_eq!(a, a + b - b);
+            assert_eq!(Fp6::ZERO, a + -a);
+            assert_eq!(fp6_mul_big(&a_big, &b_big), fp6_to_big(&(a * b)));
+            assert_eq!(fp6_mul_big(&a_big, &a_big), fp6_to_big(&a.square()));
+
+            let v = [zero.clone(), (BigUint::from(1_u64), BigUint::default()), zero.clone()];
+            assert_eq!(fp6_mul_big(&a_big, &v), fp6_to_big(&a.mul_by_nonresidue()));
+            let only_b1 = [zero.clone(), b_big[1].clone(), zero.clone()];
+            assert_eq!(fp6_mul_big(&a_big, &only_b1), fp6_to_big(&a.mul_by_1(&b.c1)));
+            let b0_and_b1 = [b_big[0].clone(), b_big[1].clone(), zero.clone()];
+            assert_eq!(fp6_mul_big(&a_big, &b0_and_b1), fp6_to_big(&a.mul_by_01(&b.c0, &b.c1)));
+
+            let (a_inv, is_zero) = a.invert();
+            assert!(!bool::from(is_zero));
+            assert_eq!(Fp6::ONE, a * a_inv);
+
+            assert_eq!(a.frobenius_map(1).frobenius_map(1), a.frobenius_map(2));
+            assert_eq!(a.frobenius_map(2).frobenius_map(3), a.frobenius_map(5));
+            assert_eq!(a, a.frobenius_map(6));
+            assert_eq!(a * b, Fp6::conditional_select(&a, &(a * b), Choice::from_bit(1)));
+            assert!(bool::from(a.ct_eq(&a)));
+            assert!(!bool::from(a.ct_eq(&b)));
+        }
+
+        // The tabulated Frobenius coefficients are (1 + u)^((N^k - 1)/3) and its square
+        let xi = (BigUint::from(1_u64), BigUint::from(1_u64));
+        for k in 0..6_u32 {
+            let exponent = (n.pow(k) - BigUint::from(1_u64)) / BigUint::from(3_u64);
+            let gamma = fp2_pow_big(&xi, &exponent);
+            assert_eq!(gamma, fp2_to_big(&FROBENIUS_COEFF_C1[k as usize]));
+            assert_eq!(fp2_mul_big(&gamma, &gamma), fp2_to_big(&FROBENIUS_COEFF_C2[k as usize]));
+        }
+
+        // The Frobenius map really is exponentiation by N
+        let a_big = rnd_big_fp6();
+        let a = fp6_from_big(&a_big);
+        assert_eq!(fp6_pow_big(&a_big, n), fp6_to_big(&a.frobenius_map(1)));
+
+        assert!(bool::from(Fp6::ZERO.invert().1));
+        assert!(bool::from(Fp6::ZERO.ct_is_zero()));
+        let v = Fp6::new(Fp2::ZERO, Fp2::ONE, Fp2::ZERO);
+        assert_eq!(Fp6::new(Fp2::ONE.mul_by_nonresidue(), Fp2::ZERO, Fp2::ZERO), v * v * v);
+        check_sum_product(|x| Fp6::new(Fp2::new(Fp::from_u64(x), Fp::ZERO), Fp2::ZERO, Fp2::ZERO));
+    }
+
+    // Fp12 elements as coefficients of 1 and w, each a BigFp6, for use as a naive reference
+    type BigFp12 = [BigFp6; 2];
+
+    fn rnd_big_fp12() -> BigFp12 {
+        [rnd_big_fp6(), rnd_big_fp6()]
+    }
+
+    fn fp12_from_big(x: &BigFp12) -> Fp12 {
+        Fp12::new(fp6_from_big(&x[0]), fp6_from_big(&x[1]))
+    }
+
+    fn fp12_to_big(x: &Fp12) -> BigFp12 {
+        [fp6_to_big(&x.c0), fp6_to_big(&x.c1)]
+    }
+
+    fn fp6_add_big(a: &BigFp6, b: &BigFp6) -> BigFp6 {
+        [fp2_add_big(&a[0], &b[0]), fp2_add_big(&a[1], &b[1]), fp2_add_big(&a[2], &b[2])]
+    }
+
+    // Schoolbook (a0 + a1*w)(b0 + b1*w) with w^2 = v
+    fn fp12_mul_big(a: &BigFp12, b: &BigFp12) -> BigFp12 {
+        let zero = (BigUint::default(), BigUint::default());
+        let v = [zero.clone(), (BigUint::from(1_u64), BigUint::default()), zero];
+        let a1b1_v = fp6_mul_big(&fp6_mul_big(&a[1], &b[1]), &v);
+        [
+            fp6_add_big(&fp6_mul_big(&a[0], &b[0]), &a1b1_v),
+            fp6_add_big(&fp6_mul_big(&a[0], &b[1]), &fp6_mul_big(&a[1], &b[0])),
+        ]
+    }
+
+    fn fp12_pow_big(a: &BigFp12, exponent: &BigUint) -> BigFp12 {
+        let mut result = fp12_to_big(&Fp12::ONE);
+        for i in (0..exponent.bits()).rev() {
+            result = fp12_mul_big(&result, &result);
+            if exponent.bit(i) {
+                result = fp12_mul_big(&result, a);
+            }
+        }
+        result
+    }
+
+    #[test]
+    fn test_fp12_ops() {
+        let n = &(*MODULUS);
+        for _i in 0..2_000 {
+            let (a_big, b_big) = (rnd_big_fp12(), rnd_big_fp12());
+            let (a, b) = (fp12_from_big(&a_big), fp12_from_big(&b_big));
+            assert_eq!(a_big, fp12_to_big(&a));
+
+            assert_eq!(
+                [fp6_add_big(&a_big[0], &b_big[0]), fp6_add_big(&a_big[1], &b_big[1])],
+                fp12_to_big(&(a + b))
+            );
+            assert_eq!(a, a + b - b);
+            assert_eq!(Fp12::ZERO, a + -a);
+            assert_eq!(fp12_mul_big(&a_big, &b_big), fp12_to_big(&(a * b)));
+            assert_eq!(fp12_mul_big(&a_big, &a_big), fp12_to_big(&a.square()));
+
+            let (a_inv, is_zero) = a.invert();
+            assert!(!bool::from(is_zero));
+            assert_eq!(Fp12::ONE, a * a_inv);
+
+            // a^(N^6 - 1) lies in the cyclotomic subgroup, where conjugation inverts
+            let unitary = a.conjugate() * a_inv;
+            assert_eq!(a.frobenius_map(6), a.conjugate());
+            assert_eq!(Fp12::ONE, unitary * unitary.conjugate());
+            assert_eq!(unitary.invert().0, unitary.conjugate());
+
+            assert_eq!(a.frobenius_map(1).frobenius_map(1), a.frobenius_map(2));
+            assert_eq!(a.frobenius_map(1).frobenius_map(2), a.frobenius_map(3));
+            assert_eq!(a.frobenius_map(3).frobenius_map(3), a.frobenius_map(6));
+            assert_eq!(a.frobenius_map(6).frobenius_map(6), a);
+            assert_eq!(a.frobenius_map(2) * b.frobenius_map(2), (a * b).frobenius_map(2));
+            assert!(bool::from(a.ct_eq(&a)));
+            assert!(!bool::from(a.ct_eq(&b)));
+            assert_eq!(b, Fp12::conditional_select(&a, &b, Choice::from_bit(1)));
+
+            // Sparse line multiplications agree with the naive product by the same sparse element
+            let (b0, b1, b3, b4) = (b.c0.c0, b.c0.c1, b.c1.c0, b.c1.c1);
+            let line_014 =
+                Fp12::new(Fp6::new(b0, b1, Fp2::ZERO), Fp6::new(Fp2::ZERO, b4, Fp2::ZERO));
+            let expected = fp12_mul_big(&a_big, &fp12_to_big(&line_014));
+            assert_eq!(expected, fp12_to_big(&a.mul_by_014(&b0, &b1, &b4)));
+            let line_034 =
+                Fp12::new(Fp6::new(b0, Fp2::ZERO, Fp2::ZERO), Fp6::new(b3, b4, Fp2::ZERO));
+            let expected = fp12_mul_big(&a_big, &fp12_to_big(&line_034));
+            assert_eq!(expected, fp12_to_big(&a.mul_by_034(&b0, &b3, &b4)));
+            assert_eq!(a.c1 * Fp6::new(b0, Fp2::ZERO, Fp2::ZERO), a.c1.mul_by_0(&b0));
+        }
+
+        // Derive the Frobenius coefficients (1 + u)^((N^k - 1)/6) independently of the table
+        let xi = (BigUint::from(1_u64), BigUint::from(1_u64));
+        for k in 0..6_u32 {
+            let exponent = (n.pow(k) - BigUint::from(1_u64)) / BigUint::from(6_u64);
+            let gamma = fp2_pow_big(&xi, &exponent);
+            assert_eq!(gamma, fp2_to_big(&fp12::FROBENIUS_COEFF_C1[k as usize]));
+            let gamma_squared = fp2_mul_big(&gamma, &gamma);
+            assert_eq!(gamma_squared, fp2_to_big(&FROBENIUS_COEFF_C1[k as usize]));
+        }
+
+        // Frobenius powers 1, 2, 3 and 6 really are exponentiation by N, N^2, N^3 and N^6
+        let a_big = rnd_big_fp12();
+        let a = fp12_from_big(&a_big);
+        let mut expected = a_big;
+        for k in 1..=6 {
+            expected = fp12_pow_big(&expected, n);
+            if [1, 2, 3, 6].contains(&k) {
+                assert_eq!(expected, fp12_to_big(&a.frobenius_map(k)));
+            }
+        }
+
+        assert!(bool::from(Fp12::ZERO.invert().1));
+        assert!(bool::from(Fp12::ZERO.ct_is_zero()));
+        let w = Fp12::new(Fp6::ZERO, Fp6::ONE);
+        assert_eq!(Fp12::new(Fp6::ONE.mul_by_nonresidue(), Fp6::ZERO), w.square());
+        check_sum_product(|x| {
+            Fp12::new(
+                Fp6::new(Fp2::new(Fp::from_u64(x), Fp::ZERO), Fp2::ZERO, Fp2::ZERO),
+                Fp6::ZERO,
+            )
+        });
+    }
+
+    // Map into the cyclotomic subgroup via the easy part of the final exponentiation, N^6 - 1
+    // then N^2 + 1, whose result satisfies f^(N^4 - N^2 + 1) = 1
+    fn rnd_cyclotomic() -> Fp12 {
+        let a = fp12_from_big(&rnd_big_fp12());
+        let f = a.conjugate() * a.invert().0;
+        f.frobenius_map(2) * f
+    }
+
+    #[test]
+    fn test_cyclotomic_squaring() {
+        for _i in 0..500 {
+            let f = rnd_cyclotomic();
+            assert_eq!(f.square(), f.cyclotomic_square());
+            assert_eq!(Fp12::ONE, f * f.conjugate());
+
+            // A run of compressed squarings, decompressed once, matches squaring throughout
+            let mut expected = f;
+            let mut compressed = f.compress();
+            let mut runs = Vec::new();
+            for _j in 0..5 {
+                expected = expected.cyclotomic_square();
+                compressed = compressed.square();
+                runs.push(compressed);
+                assert_eq!(expected.compress(), compressed);
+            }
+            assert_eq!(expected, compressed.decompress());
+            assert_eq!(f, f.compress().decompress());
+
+            // Batched decompression agrees, including for the g4 = 0 case of one
+            runs.push(Fp12::ONE.compress());
+            let singles: Vec<Fp12> = runs.iter().map(Fp12Compressed::decompress).collect();
+            let mut batched = vec![Fp12::ZERO; runs.len()];
+            Fp12Compressed::batch_decompress(&runs, &mut batched);
+            assert_eq!(singles, batched);
+        }
+
+        assert_eq!(Fp12::ONE, Fp12::ONE.cyclotomic_square());
+        assert_eq!(Fp12Compressed::default(), Fp12::ONE.compress());
+        assert_eq!(Fp12::ONE, Fp12::ONE.compress().square().decompress());
+        Fp12Compressed::batch_decompress(&[], &mut []);
+    }
+
+    // Square root in Fp2 via the norm, since -1 is a non-residue in Fp; None when there is none
+    fn fp2_sqrt(x: &Fp2) -> Option<Fp2> {
+        let fp_sqrt = |a: &Fp| {
+            let mut root = W6x64::default();
+            let is_square = fe_sqrt(&mut root, a.as_mont());
+            Some(Fp::from_mont_unchecked(root.v)).filter(|_| bool::from(is_square))
+        };
+        let half = Fp::from_u64(2).invert().0;
+        let gamma = fp_sqrt(&x.norm())?;
+        let x0 = fp_sqrt(&((x.c0 + gamma) * half)).or_else(|| fp_sqrt(&((x.c0 - gamma) * half)))?;
+        let root = Fp2::new(x0, x.c1 * (x0 + x0).invert().0);
+        Some(root).filter(|root| root.square() == *x)
+    }
+
+    // A cyclotomic element with g3 = c1.c0 = 0 but g1, g2 and g5 non-zero, from a random k; Setting
+    // the g3 terms to zero in the Granger-Scott identities (over Fp4 = Fp2[s], as in fp12_alt.rs)
+    // leaves g1 = 3k^2 / 2(E + k^3), g0 = 1 - k*g1, g5 = g1 * sqrt((1 + 3g0) / E(1 - g0)),
+    // g4 = g5(1 - g0) / g1 and g2 = 2g1^2 / (1 - g0), with E = 1 + u; None without the square root
+    fn cyclotomic_with_zero_g3(k: &Fp2) -> Option<Fp12> {
+        let e = Fp2::ONE.mul_by_nonresidue();
+        let (two, three) =
+            (Fp2::new(Fp::from_u64(2), Fp::ZERO), Fp2::new(Fp::from_u64(3), Fp::ZERO));
+        let g1 = three * k.square() * (two * (e + k.square() * k)).invert().0;
+        let g0 = Fp2::ONE - k * g1;
+        let one_minus_g0_inv = (Fp2::ONE - g0).invert().0;
+        let g5 = g1 * fp2_sqrt(&((Fp2::ONE + three * g0) * (e.invert().0 * one_minus_g0_inv)))?;
+        let g4 = g5 * (Fp2::ONE - g0) * g1.invert().0;
+        let g2 = two * g1.square() * one_minus_g0_inv;
+        Some(Fp12::new(Fp6::new(g0, g1, g2), Fp6::new(Fp2::ZERO, g4, g5)))
+    }
+
+    #[test]
+    fn test_decompression_with_zero_g3() {
+        let mut found = 0;
+        while found < 20 {
+            let f = match cyclotomic_with_zero_g3(&fp2_from_big(&rnd_big_fp2())) {
+                Some(f) => f,
+                None => continue,
+            };
+            found += 1;
+
+            // In the cyclotomic subgroup: f^(N^6 + 1) = 1 and f^(N^4 - N^2 + 1) = 1
+            assert_eq!(Fp12::ONE, f * f.conjugate());
+            assert_eq!(f.frobenius_map(2), f.frobenius_map(4) * f);
+
+            // Only the 2*g1*g5 / g2 branch of g4_fraction recovers g4 here
+            let compressed = f.compress();
+            assert!(bool::from(compressed.g3.ct_is_zero()));
+            assert!(!bool::from((compressed.g1 * compressed.g5).ct_is_zero()));
+            assert_eq!(f, compressed.decompress());
+            assert_eq!(f.cyclotomic_square(), compressed.square().decompress());
+
+            let runs = [rnd_cyclotomic().compress(), compressed, compressed.square()];
+            let mut batched = [Fp12::ZERO; 3];
+            Fp12Compressed::batch_decompress(&runs, &mut batched);
+            assert_eq!(f, batched[1]);
+            assert_eq!(runs.iter().map(Fp12Compressed::decompress).collect::<Vec<_>>(), batched);
+        }
+    }
+
+    #[test]
+    fn test_fp12_alt_tower() {
+        for _i in 0..2_000 {
+            let (a, b) = (fp12_from_big(&rnd_big_fp12()), fp12_from_big(&rnd_big_fp12()));
+            let (a_alt, b_alt) = (Fp12Alt::from(a), Fp12Alt::from(b));
+            assert_eq!(a, Fp12::from(a_alt));
+
+            // The maps are ring isomorphisms
+            assert_eq!(Fp12Alt::from(a + b), a_alt + b_alt);
+            assert_eq!(Fp12Alt::from(a - b), a_alt - b_alt);
+            assert_eq!(Fp12Alt::from(-a), -a_alt);
+            assert_eq!(Fp12Alt::from(a * b), a_alt * b_alt);
+            assert_eq!(Fp12Alt::from(a.square()), a_alt.square());
+            assert!(bool::from(a_alt.ct_eq(&a_alt)));
+            assert!(!bool::from(a_alt.ct_eq(&b_alt)));
+
+            let f = rnd_cyclotomic();
+            let f_alt = Fp12Alt::from(f);
+            assert_eq!(Fp12Alt::from(f.cyclotomic_square()), f_alt.cyclotomic_square());
+            assert_eq!(f_alt.square(), f_alt.cyclotomic_square());
+
+            let (a4, b4) = (a_alt.c0, b_alt.c1);
+            assert_eq!(Fp4::ONE, a4 * a4.invert().0);
+            assert_eq!(a4 * a4, a4.square());
+            assert_eq!(a4 * b4, b4 * a4);
+            assert_eq!(Fp4::new(Fp2::ZERO, Fp2::ONE) * a4, a4.mul_by_nonresidue());
+        }
+
+        // t^3 = s and s^2 = 1 + u, matching w^3 and w^6 in the standard tower
+        let t = Fp12Alt::new(Fp4::ZERO, Fp4::ONE, Fp4::ZERO);
+        let s = Fp12Alt::new(Fp4::new(Fp2::ZERO, Fp2::ONE), Fp4::ZERO, Fp4::ZERO);
+        let w = Fp12::new(Fp6::ZERO, Fp6::ONE);
+        assert_eq!(s, t * t * t);
+        assert_eq!(Fp12Alt::from(w), t);
+        assert_eq!(Fp12::from(s), w * w * w);
+        assert_eq!(Fp12Alt::ONE, Fp12Alt::from(Fp12::ONE));
+        assert_eq!(Fp12Alt::ZERO, Fp12Alt::from(Fp12::ZERO));
+        assert!(bool::from(Fp4::ZERO.invert().1));
+    }
+
+    #[test]
+    fn test_generated_asm_fields() {
+        // Every modulus in moduli.rs has generated assembly attached, used whenever it is built
+        assert_eq!(Some(AsmMul::Bls12_381Fr), BLS12_381_FR.asm_mul);
+        let order = BigUint::from_str_radix(
+            "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001",
+            16,
+        )
+        .unwrap();
+        check_mont_field(&BLS12_381_FR, &order);
+
+        // Ed448-Goldilocks 2^448 - 2^224 - 1 in seven limbs
+        let one = BigUint::from(1_u64);
+        let p448 = (&one << 448) - (&one << 224) - &one;
+        assert_eq!(Some(AsmMul::P448), P448_FP.asm_mul);
+        assert_eq!(mont_field_from_big(&p448), P448_FP);
+        check_mont_field(&P448_FP, &p448);
+
+        // 2^512 - 569 in eight limbs, the most the generator handles
+        let p512 = (&one << 512) - BigUint::from(569_u64);
+        assert_eq!(Some(AsmMul::P512), P512_FP.asm_mul);
+        assert_eq!(mont_field_from_big(&p512), P512_FP);
+        check_mont_field(&P512_FP, &p512);
+    }
+
+    // The constants typed into an assembly listing: the .quad limbs after the modulus label, and
+    // N' as either the immediate build.rs loads into %rdx before each reduction step or the NPRIME
+    // symbol of the hand-written files
+    #[cfg(all(feature = "asm", target_arch = "x86_64"))]
+    fn asm_constants(listing: &str) -> (Vec<u64>, Vec<u64>) {
+        let hex = |x: &str| u64::from_str_radix(x.trim().trim_start_matches("0x"), 16).unwrap();
+        let n = listing.lines().filter_map(|l| l.strip_prefix(".quad")).map(hex).collect();
+        let generated = listing
+            .lines()
+            .filter_map(|l| l.trim().strip_prefix("movabsq $"))
+            .filter_map(|l| l.strip_suffix(", %rdx"));
+        let hand_written = listing.lines().filter_map(|l| l.strip_prefix(".set NPRIME,"));
+        (n, generated.chain(hand_written).map(hex).collect())
+    }
+
+    // Assembly and portable multiplication agree on every pair drawn from 0, 1, R mod N and N - 1
+    #[cfg(all(feature = "asm", target_arch = "x86_64"))]
+    fn check_asm_boundaries<const LIMBS: usize>(field: &MontField<LIMBS>) {
+        if !Backend::Asm.is_supported() {
+            return;
+        }
+        let (mut one, mut n_minus_one) = ([0_u64; LIMBS], field.n);
+        one[0] = 1;
+        n_minus_one[0] -= 1; // N is odd
+        let boundaries = [[0_u64; LIMBS], one, field.r_mod_n, n_minus_one];
+        let (mut expected, mut actual) = ([0_u64; LIMBS], [0_u64; LIMBS]);
+        for a in &boundaries {
+            for b in &boundaries {
+                field.mont_mul_portable(&mut expected, a, b);
+                field.mont_mul_asm(&mut actual, a, b);
+                assert_eq!(expected, actual);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(all(feature = "asm", target_arch = "x86_64"))]
+    fn test_generated_asm_constants() {
+        macro_rules! check {
+            ($field:expr, $name:literal) => {
+                let listing = include_str!(concat!(env!("OUT_DIR"), "/mont_mul_asm_", $name, ".S"));
+                let (n, n_primes) = asm_constants(listing);
+                assert_eq!(&$field.n[..], &n[..], "{}", $name);
+                assert_eq!(n_primes.len(), $field.n.len(), "{}", $name);
+                assert!(n_primes.iter().all(|&x| x == $field.n_prime), "{}", $name);
+                check_asm_boundaries(&$field);
+            };
+        }
+        check!(BLS12_381_FR, "bls12_381_r");
+        check!(BN254_FQ, "bn254_q");
+        check!(BN254_FR, "bn254_r");
+        check!(BLS12_377_FQ, "bls12_377_q");
+        check!(BLS12_377_FR, "bls12_377_r");
+        check!(P448_FP, "p448");
+        check!(P512_FP, "p512");
+
+        // The hand-written BLS12-381 assembly has its constants typed in rather than generated
+        for listing in &[include_str!("mont_mul_asm.S"), include_str!("mont_sqr_asm.S")] {
+            let (n, n_primes) = asm_constants(listing);
+            assert_eq!(&BLS12_381_FP.n[..], &n[..]);
+            assert_eq!(&[BLS12_381_FP.n_prime][..], &n_primes[..]);
+        }
+        check_asm_boundaries(&BLS12_381_FP);
+    }
 }
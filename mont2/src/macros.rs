@@ -0,0 +1,77 @@
+// Copyright 2021 Eric Schorn; Licensed under the 3-Clause BSD License.
+
+// Given by-value Add, Sub, Mul and Neg for a Copy field element type with ZERO and ONE constants,
+// derive the reference and assignment variants of each operator along with Sum and Product
+macro_rules! impl_field_ops {
+    ($t:ident) => {
+        impl_field_ops!(@binary $t, Add, add, AddAssign, add_assign);
+        impl_field_ops!(@binary $t, Sub, sub, SubAssign, sub_assign);
+        impl_field_ops!(@binary $t, Mul, mul, MulAssign, mul_assign);
+
+        impl core::ops::Neg for &$t {
+            type Output = $t;
+            fn neg(self) -> $t {
+                -*self
+            }
+        }
+
+        impl core::iter::Sum for $t {
+            fn sum<I: Iterator<Item = $t>>(iter: I) -> $t {
+                iter.fold($t::ZERO, |acc, x| acc + x)
+            }
+        }
+
+        impl<'a> core::iter::Sum<&'a $t> for $t {
+            fn sum<I: Iterator<Item = &'a $t>>(iter: I) -> $t {
+                iter.fold($t::ZERO, |acc, x| acc + x)
+            }
+        }
+
+        impl core::iter::Product for $t {
+            fn product<I: Iterator<Item = $t>>(iter: I) -> $t {
+                iter.fold($t::ONE, |acc, x| acc * x)
+            }
+        }
+
+        impl<'a> core::iter::Product<&'a $t> for $t {
+            fn product<I: Iterator<Item = &'a $t>>(iter: I) -> $t {
+                iter.fold($t::ONE, |acc, x| acc * x)
+            }
+        }
+    };
+
+    (@binary $t:ident, $trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident) => {
+        impl<'a> core::ops::$trait<&'a $t> for $t {
+            type Output = $t;
+            fn $method(self, rhs: &'a $t) -> $t {
+                core::ops::$trait::$method(self, *rhs)
+            }
+        }
+
+        impl<'a> core::ops::$trait<$t> for &'a $t {
+            type Output = $t;
+            fn $method(self, rhs: $t) -> $t {
+                core::ops::$trait::$method(*self, rhs)
+            }
+        }
+
+        impl<'a, 'b> core::ops::$trait<&'b $t> for &'a $t {
+            type Output = $t;
+            fn $method(self, rhs: &'b $t) -> $t {
+                core::ops::$trait::$method(*self, *rhs)
+            }
+        }
+
+        impl core::ops::$assign_trait for $t {
+            fn $assign_method(&mut self, rhs: $t) {
+                *self = core::ops::$trait::$method(*self, rhs);
+            }
+        }
+
+        impl<'a> core::ops::$assign_trait<&'a $t> for $t {
+            fn $assign_method(&mut self, rhs: &'a $t) {
+                *self = core::ops::$trait::$method(*self, *rhs);
+            }
+        }
+    };
+}
@@ -0,0 +1,45 @@
+// Copyright 2021 Eric Schorn; Licensed under the 3-Clause BSD License.
+
+// Moduli beyond the BLS12-381 base field, least significant limb first; Shared by field.rs and by
+// build.rs, which generates a multiplication routine in assembly for each
+
+#[rustfmt::skip]
+// BLS12-381 group order r
+pub const BLS12_381_R: [u64; 4] = [
+    0xffff_ffff_0000_0001, 0x53bd_a402_fffe_5bfe, 0x3339_d808_09a1_d805, 0x73ed_a753_299d_7d48,
+];
+
+#[rustfmt::skip]
+// BN254 (alt_bn128) base field modulus q and group order r, as used by the Ethereum precompiles
+pub const BN254_Q: [u64; 4] = [
+    0x3c20_8c16_d87c_fd47, 0x9781_6a91_6871_ca8d, 0xb850_45b6_8181_585d, 0x3064_4e72_e131_a029,
+];
+#[rustfmt::skip]
+pub const BN254_R: [u64; 4] = [
+    0x43e1_f593_f000_0001, 0x2833_e848_79b9_7091, 0xb850_45b6_8181_585d, 0x3064_4e72_e131_a029,
+];
+
+#[rustfmt::skip]
+// BLS12-377 base field modulus q (377 bits, so six limbs) and group order r
+pub const BLS12_377_Q: [u64; 6] = [
+    0x8508_c000_0000_0001, 0x170b_5d44_3000_0000, 0x1ef3_622f_ba09_4800,
+    0x1a22_d9f3_00f5_138f, 0xc63b_05c0_6ca1_493b, 0x01ae_3a46_17c5_10ea,
+];
+#[rustfmt::skip]
+pub const BLS12_377_R: [u64; 4] = [
+    0x0a11_8000_0000_0001, 0x59aa_76fe_d000_0001, 0x60b4_4d1e_5c37_b001, 0x12ab_655e_9a2c_a556,
+];
+
+#[rustfmt::skip]
+// The Ed448-Goldilocks prime 2^448 - 2^224 - 1, which uses every bit of its seven limbs
+pub const P448: [u64; 7] = [
+    0xffff_ffff_ffff_ffff, 0xffff_ffff_ffff_ffff, 0xffff_ffff_ffff_ffff, 0xffff_fffe_ffff_ffff,
+    0xffff_ffff_ffff_ffff, 0xffff_ffff_ffff_ffff, 0xffff_ffff_ffff_ffff,
+];
+
+#[rustfmt::skip]
+// 2^512 - 569, the largest prime that fits in eight limbs
+pub const P512: [u64; 8] = [
+    0xffff_ffff_ffff_fdc7, 0xffff_ffff_ffff_ffff, 0xffff_ffff_ffff_ffff, 0xffff_ffff_ffff_ffff,
+    0xffff_ffff_ffff_ffff, 0xffff_ffff_ffff_ffff, 0xffff_ffff_ffff_ffff, 0xffff_ffff_ffff_ffff,
+];